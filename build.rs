@@ -0,0 +1,81 @@
+//! Generates a test for every example in `tests/fixtures/YYYY/dayNN/*.txt`, which the tests of `src/examples.rs`
+//! include. Adding an example and its answers is enough to test it against every solver of its day.
+//!
+//! Also sets `BUILD_COMMIT` to the commit the binary is built from, which the history records with every run.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-changed=tests/fixtures");
+    let mut tests = String::new();
+    for (year, day, name) in examples(Path::new("tests/fixtures")) {
+        let function = format!("y{}_day{:02}_{}", year, day, name.replace(|c: char| !c.is_ascii_alphanumeric(), "_"));
+        writeln!(tests, "#[test]\nfn {}() {{\n    check({}, {}, {:?});\n}}\n", function, year, day, name).unwrap();
+    }
+    let out = PathBuf::from(std::env::var("OUT_DIR").expect("cargo sets OUT_DIR"));
+    fs::write(out.join("example_tests.rs"), tests).expect("wrote the example tests");
+
+    // The commit changes with HEAD and the index, and whether it is dirty with the sources.
+    for path in [".git/HEAD", ".git/index"].into_iter().filter(|path| Path::new(path).exists()) {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rustc-env=BUILD_COMMIT={}", commit());
+}
+
+/// The abbreviated hash of the checked out commit, with `-dirty` if there are uncommitted changes, or `unknown`
+/// outside of a git checkout.
+fn commit() -> String {
+    let git = |args: &[&str]| {
+        // Without optional locks, `git status` doesn't refresh the index, which would run this script again.
+        std::process::Command::new("git")
+            .arg("--no-optional-locks")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    match git(&["rev-parse", "--short", "HEAD"]) {
+        Some(hash) if !hash.is_empty() => match git(&["status", "--porcelain", "--untracked-files=no"]) {
+            Some(changes) if !changes.is_empty() => format!("{}-dirty", hash),
+            _ => hash,
+        },
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Returns the year, day and name of every example, in the order of their paths.
+fn examples(dir: &Path) -> Vec<(u16, u8, String)> {
+    let mut examples = vec![];
+    for year_dir in entries(dir) {
+        let Some(year) = file_name(&year_dir).and_then(|year| year.parse::<u16>().ok()) else { continue };
+        for day_dir in entries(&year_dir) {
+            let Some(day) = file_name(&day_dir).and_then(|day| day.strip_prefix("day")?.parse::<u8>().ok()) else { continue };
+            for file in entries(&day_dir) {
+                if file.extension().map(|extension| extension == "txt").unwrap_or(false) {
+                    let name = file.file_stem().expect("a file name").to_string_lossy().to_string();
+                    examples.push((year, day, name));
+                }
+            }
+        }
+    }
+    examples
+}
+
+/// Returns the paths in `dir` sorted, or none if it can't be read.
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect(),
+        Err(_) => vec![],
+    };
+    entries.sort();
+    entries
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|name| name.to_str())
+}
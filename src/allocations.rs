@@ -0,0 +1,182 @@
+use std::fmt::{Display, Formatter};
+
+/// The allocations made while running a step on one thread.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Allocations {
+    /// The number of allocations, including reallocations.
+    pub(crate) count: u64,
+    /// The bytes allocated, whether they were freed again or not.
+    pub(crate) bytes: u64,
+    /// The most bytes that were live at once, beyond those live before the step.
+    pub(crate) peak: u64,
+}
+
+impl Display for Allocations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} allocs, {} total, {} peak", self.count, bytes(self.bytes), bytes(self.peak))
+    }
+}
+
+/// Formats `n` bytes in the largest binary unit that keeps it above 1.
+fn bytes(n: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+    let mut value = n as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Runs `f`, returning the allocations it made on the current thread if they are counted.
+#[cfg(feature = "count-allocations")]
+pub(crate) fn track<T>(f: impl FnOnce() -> T) -> (T, Option<Allocations>) {
+    let before = counting::reset_peak();
+    let result = f();
+    let after = counting::counters();
+    let allocations = Allocations {
+        count: after.count - before.count,
+        bytes: after.bytes - before.bytes,
+        peak: (after.peak - before.live).max(0) as u64,
+    };
+    (result, Some(allocations))
+}
+
+/// Runs `f`. Allocations are only counted when built with the `count-allocations` feature.
+#[cfg(not(feature = "count-allocations"))]
+pub(crate) fn track<T>(f: impl FnOnce() -> T) -> (T, Option<Allocations>) {
+    (f(), None)
+}
+
+#[cfg(feature = "count-allocations")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    /// The system allocator, counting the allocations of every thread separately, so that days running in
+    /// parallel don't count each other's allocations.
+    struct Counting;
+
+    #[global_allocator]
+    static ALLOCATOR: Counting = Counting;
+
+    #[derive(Clone, Copy, Debug)]
+    pub(super) struct Counters {
+        pub(super) count: u64,
+        pub(super) bytes: u64,
+        /// Memory freed by another thread than the one that allocated it makes this negative.
+        pub(super) live: i64,
+        pub(super) peak: i64,
+    }
+
+    thread_local! {
+        static COUNTERS: Cell<Counters> = const { Cell::new(Counters { count: 0, bytes: 0, live: 0, peak: 0 }) };
+    }
+
+    pub(super) fn counters() -> Counters {
+        COUNTERS.with(Cell::get)
+    }
+
+    /// Restarts the peak at the bytes live now, returning the counters.
+    pub(super) fn reset_peak() -> Counters {
+        COUNTERS.with(|counters| {
+            let mut current = counters.get();
+            current.peak = current.live;
+            counters.set(current);
+            current
+        })
+    }
+
+    fn record(allocated: usize, freed: usize) {
+        // Ignores allocations while the thread is torn down.
+        let _ = COUNTERS.try_with(|counters| {
+            let mut current = counters.get();
+            if allocated > 0 {
+                current.count += 1;
+                current.bytes += allocated as u64;
+            }
+            current.live += allocated as i64 - freed as i64;
+            current.peak = current.peak.max(current.live);
+            counters.set(current);
+        });
+    }
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                record(layout.size(), 0);
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                record(layout.size(), 0);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            record(0, layout.size());
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                record(new_size, layout.size());
+            }
+            new_ptr
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(bytes(0), "0 B");
+        assert_eq!(bytes(1023), "1023 B");
+        assert_eq!(bytes(1536), "1.5 KiB");
+        assert_eq!(bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn test_display() {
+        let allocations = Allocations { count: 12, bytes: 2048, peak: 512 };
+        assert_eq!(allocations.to_string(), "12 allocs, 2.0 KiB total, 512 B peak");
+    }
+
+    #[cfg(feature = "count-allocations")]
+    #[test]
+    fn test_track() {
+        let (sum, allocations) = track(|| {
+            let mut total = 0;
+            for n in 1..=4 {
+                let values = vec![n; 1000];
+                total += values.iter().sum::<u64>();
+            }
+            total
+        });
+        assert_eq!(sum, 10_000);
+        let allocations = allocations.unwrap();
+        assert_eq!(allocations.count, 4);
+        assert_eq!(allocations.bytes, 4 * 8000);
+        // Each vector is freed before the next one is allocated.
+        assert_eq!(allocations.peak, 8000);
+    }
+
+    #[cfg(not(feature = "count-allocations"))]
+    #[test]
+    fn test_track_uncounted() {
+        assert_eq!(track(|| 7), (7, None));
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use crate::problem::Answer;
+
+/// Default location of the known answers of `year`.
+pub(crate) fn default_path(year: u16) -> String {
+    format!("answers/{}.toml", year)
+}
+
+/// The name of the answers of the input at `path`: its file name without extension.
+pub(crate) fn input_name(path: &str) -> String {
+    std::path::Path::new(path).file_stem().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Known answers of one year by day and part, read from a TOML file such as:
+///
+/// ```toml
+/// [day01]
+/// part1 = 1715
+/// part2 = 1739
+///
+/// [day01.teammate]
+/// part1 = 1624
+///
+/// [day13]
+/// part2 = """
+/// #..#
+/// .##."""
+/// ```
+///
+/// Tables named `dayNN` hold the answers of the puzzle input, and `dayNN.<name>` those of another input, given as
+/// `<name>.txt`. Only integer or string `part1` and `part2` keys are supported. Multi-line strings are image answers.
+/// The answers of a single day, such as those of an example, are bare keys without a table.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct Answers(BTreeMap<(u8, Option<String>, u8), Answer>);
+
+/// The outcome of checking an answer against the known one.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum Verdict<'a> {
+    Pass,
+    Fail { expected: &'a Answer },
+    /// There is no known answer for the part.
+    Unknown,
+}
+
+impl Answers {
+    pub(crate) fn load(path: &str) -> Result<Answers, String> {
+        let inp = std::fs::read_to_string(path).map_err(|e| format!("Failed to read answers from '{}': {}", path, e))?;
+        Answers::parse(&inp).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Reads the answers of the puzzle input of `day`, without tables.
+    pub(crate) fn load_day(path: &str, day: u8) -> Result<Answers, String> {
+        let inp = std::fs::read_to_string(path).map_err(|e| format!("Failed to read answers from '{}': {}", path, e))?;
+        Answers::parse_day(&inp, day).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub(crate) fn parse(inp: &str) -> Result<Answers, String> {
+        Answers::parse_tables(inp, None)
+    }
+
+    pub(crate) fn parse_day(inp: &str, day: u8) -> Result<Answers, String> {
+        Answers::parse_tables(inp, Some(day))
+    }
+
+    /// Parses the answers of a year in tables, or the bare answers of `single_day`.
+    fn parse_tables(inp: &str, single_day: Option<u8>) -> Result<Answers, String> {
+        let mut answers = Answers::default();
+        let mut day = single_day.map(|day| (day, None));
+        let mut lines = inp.lines().enumerate();
+        while let Some((number, line)) = lines.next() {
+            let error = |reason: &str| format!("line {}: {} ('{}')", number + 1, reason, line);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(table) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                if single_day.is_some() {
+                    return Err(error("expected 'partN = <answer>' without a table, as the answers are of a single day"));
+                }
+                let (name, input) = match table.trim().split_once('.') {
+                    Some((name, input)) => (name, Some(input.trim().trim_matches('"').to_string())),
+                    None => (table.trim(), None),
+                };
+                let parsed = name.strip_prefix("day").and_then(|day| day.parse::<u8>().ok());
+                if input.as_deref() == Some("") {
+                    return Err(error("expected a [dayNN.<input>] table"));
+                }
+                day = Some((parsed.ok_or_else(|| error("expected a [dayNN] table"))?, input));
+                continue;
+            }
+
+            let (day, input) = day.clone().ok_or_else(|| error("expected a [dayNN] table before any answers"))?;
+            let (key, value) = line.split_once('=').ok_or_else(|| error("expected 'partN = <answer>'"))?;
+            let part = match key.trim() {
+                "part1" => 1,
+                "part2" => 2,
+                _ => return Err(error("expected part1 or part2")),
+            };
+            let value = value.trim();
+            let answer = if let Some(first) = value.strip_prefix("\"\"\"") {
+                // A multi-line string, where a newline directly after the opening quotes is dropped.
+                let mut text = first.to_string();
+                while !text.ends_with("\"\"\"") {
+                    let (_, line) = lines.next().ok_or_else(|| error("unterminated multi-line string"))?;
+                    if !text.is_empty() {
+                        text.push('\n');
+                    }
+                    text.push_str(line);
+                }
+                Answer::image(unescape(&text[..text.len() - 3]).map_err(|reason| error(&reason))?.as_str())
+            } else if let Some(text) = value.strip_prefix('"') {
+                let text = closing_quote(text).map(|end| &text[..end]).ok_or_else(|| error("unterminated string"))?;
+                Answer::Text(unescape(text).map_err(|reason| error(&reason))?)
+            } else {
+                let value = value.split('#').next().unwrap().trim().replace('_', "");
+                Answer::Integer(value.parse::<i64>().map_err(|_| error("expected an integer or a string"))?)
+            };
+            if answers.0.insert((day, input, part), answer).is_some() {
+                return Err(error("duplicate answer"));
+            }
+        }
+        Ok(answers)
+    }
+
+    /// The known answer of the puzzle input.
+    pub(crate) fn get(&self, day: u8, part: u8) -> Option<&Answer> {
+        self.0.get(&(day, None, part))
+    }
+
+    /// Returns the answers of the input named `input` as those of the puzzle input, to check the answers to it.
+    pub(crate) fn input(self, input: &str) -> Answers {
+        let answers = self.0.into_iter().filter(|((_, name, _), _)| name.as_deref() == Some(input));
+        Answers(answers.map(|((day, _, part), answer)| ((day, None, part), answer)).collect())
+    }
+
+    pub(crate) fn verify(&self, day: u8, part: u8, answer: &Answer) -> Verdict<'_> {
+        match self.get(day, part) {
+            Some(expected) if expected == answer => Verdict::Pass,
+            Some(expected) => Verdict::Fail { expected },
+            None => Verdict::Unknown,
+        }
+    }
+}
+
+/// Returns the index of the first unescaped `"` in `text`.
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' if !escaped => return Some(i),
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Resolves the escapes of a TOML basic string.
+fn unescape(text: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            other => return Err(format!("unsupported escape '\\{}'", other.map(String::from).unwrap_or_default())),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# known answers
+[day01]
+part1 = 1_715 # first star
+part2 = \"ab\\\"c\"
+
+[day13]
+part2 = \"\"\"
+#..#
+.##.\"\"\"
+",
+        )
+        .unwrap();
+        assert_eq!(answers.get(1, 1), Some(&Answer::Integer(1715)));
+        assert_eq!(answers.get(1, 2), Some(&Answer::Text("ab\"c".to_string())));
+        assert_eq!(answers.get(13, 1), None);
+        assert_eq!(answers.get(13, 2), Some(&Answer::image("#..#\n.##.")));
+    }
+
+    #[test]
+    fn test_input() {
+        let answers = Answers::parse("[day01]\npart1 = 7\n\n[day01.teammate]\npart1 = 8\n\n[day02.\"other.v2\"]\npart2 = 9").unwrap();
+        assert_eq!(answers.get(1, 1), Some(&Answer::Integer(7)));
+        assert_eq!(answers.get(2, 2), None);
+        let other = Answers::parse("[day01]\npart1 = 7\n\n[day02.\"other.v2\"]\npart2 = 9").unwrap().input("other.v2");
+        assert_eq!(other.get(1, 1), None);
+        assert_eq!(other.get(2, 2), Some(&Answer::Integer(9)));
+        let teammate = answers.input("teammate");
+        assert_eq!(teammate.verify(1, 1, &Answer::Integer(7)), Verdict::Fail { expected: &Answer::Integer(8) });
+        assert_eq!(teammate.verify(1, 2, &Answer::Integer(7)), Verdict::Unknown);
+        assert!(Answers::parse("[day01.]\npart1 = 1").is_err());
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        assert_eq!(Answers::parse("part1 = 1"), Err("line 1: expected a [dayNN] table before any answers ('part1 = 1')".to_string()));
+        assert_eq!(Answers::parse("[day01]\npart3 = 1"), Err("line 2: expected part1 or part2 ('part3 = 1')".to_string()));
+        assert!(Answers::parse("[first]").is_err());
+        assert!(Answers::parse("[day01]\npart1 = one").is_err());
+        assert!(Answers::parse("[day01]\npart1 = \"one").is_err());
+        assert!(Answers::parse("[day01]\npart1 = \"\"\"\none").is_err());
+        assert!(Answers::parse("[day01]\npart1 = 1\npart1 = 2").is_err());
+    }
+
+    #[test]
+    fn test_parse_day() {
+        let answers = Answers::parse_day("# the first example\npart1 = 19\npart2 = 103", 12).unwrap();
+        assert_eq!(answers.get(12, 1), Some(&Answer::Integer(19)));
+        assert_eq!(answers.get(12, 2), Some(&Answer::Integer(103)));
+        assert_eq!(answers.get(1, 1), None);
+        assert!(Answers::parse_day("[day12]\npart1 = 19", 12).is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::parse("[day07]\npart1 = 37").unwrap();
+        assert_eq!(answers.verify(7, 1, &Answer::Integer(37)), Verdict::Pass);
+        assert_eq!(answers.verify(7, 1, &Answer::Integer(36)), Verdict::Fail { expected: &Answer::Integer(37) });
+        assert_eq!(answers.verify(7, 2, &Answer::Integer(168)), Verdict::Unknown);
+    }
+
+    #[test]
+    fn test_input_name() {
+        assert_eq!(input_name("inputs/teammate.txt"), "teammate");
+        assert_eq!(input_name("other.v2.txt"), "other.v2");
+    }
+
+    #[test]
+    fn test_default_path() {
+        assert_eq!(default_path(2021), "answers/2021.toml");
+    }
+
+    #[test]
+    fn test_answers_file() {
+        let answers = Answers::parse(include_str!("../answers/2021.toml")).unwrap();
+        for (day, _, _) in answers.0.keys() {
+            assert!(crate::registry::find(2021, *day).is_some(), "day {}", day);
+        }
+        assert_eq!(answers.0.len(), 30);
+        assert!(matches!(answers.get(13, 2), Some(Answer::Image(rows)) if rows.len() == 6));
+    }
+}
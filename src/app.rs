@@ -0,0 +1,229 @@
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use crate::answers::{self, Answers};
+use crate::cli::{self, Command};
+use crate::input::Resolver;
+use crate::report::{self, Format};
+use crate::{bench, client, crosscheck, explain, history, registry, repl, runner, scaffold, serve, submit, tui, watch, PUZZLES};
+
+/// Runs the command line `args`, without the program name, and returns the exit code.
+pub fn run<I: IntoIterator<Item=String>>(args: I) -> ExitCode {
+    let command = match cli::parse(args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match command {
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            ExitCode::SUCCESS
+        }
+        Command::List => {
+            for puzzle in PUZZLES {
+                if puzzle.variants.is_empty() {
+                    println!("{} day{:02}: {}", puzzle.year, puzzle.day, puzzle.title);
+                } else {
+                    println!("{} day{:02}: {} (variants: {})", puzzle.year, puzzle.day, puzzle.title, puzzle.variant_names().join(", "));
+                }
+            }
+            ExitCode::SUCCESS
+        }
+        Command::Fetch(fetch) => {
+            let year = fetch.year.unwrap_or_else(registry::latest_year);
+            let path = Resolver::from_env(None).day_path(year, fetch.day);
+            match client::fetch(|| client::Client::from_env(fetch.base_url), year, fetch.day, &path) {
+                Ok(client::Fetched::Cached) => println!("{} day{:02}: already fetched to '{}'", year, fetch.day, path.display()),
+                Ok(client::Fetched::Downloaded) => println!("{} day{:02}: fetched to '{}'", year, fetch.day, path.display()),
+                Err(message) => {
+                    eprintln!("{}", message);
+                    return ExitCode::FAILURE;
+                }
+            }
+            ExitCode::SUCCESS
+        }
+        Command::New(new) => {
+            let title = new.title.unwrap_or_else(|| format!("Day {}", new.day));
+            let year = new.year.unwrap_or_else(registry::latest_year);
+            match scaffold::new_day(std::path::Path::new("."), year, new.day, &title) {
+                Ok(paths) => {
+                    for path in paths {
+                        println!("wrote '{}'", path.display());
+                    }
+                    ExitCode::SUCCESS
+                }
+                Err(message) => {
+                    eprintln!("{}", message);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::Submit(submit) => {
+            if submit::run(&submit) { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+        }
+        Command::Bench(bench) => {
+            let year = bench.run.year.unwrap_or_else(registry::latest_year);
+            let days = match select(year, bench.run.days.clone()).filter(|days| has_variant(year, days, bench.run.variant.as_deref())) {
+                Some(days) => days,
+                None => return ExitCode::FAILURE,
+            };
+            if bench::run(&bench, year, &days) { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+        }
+        Command::Watch(watch) => {
+            let year = watch.run.year.unwrap_or_else(registry::latest_year);
+            match select(year, watch.run.days.clone()).filter(|days| has_variant(year, days, watch.run.variant.as_deref())) {
+                Some(days) => {
+                    if watch.run.explain.as_ref().is_some_and(|explain| !explain::install(explain, &days)) {
+                        return ExitCode::FAILURE;
+                    }
+                    watch::run(&watch, year, days[0])
+                }
+                None => ExitCode::FAILURE,
+            }
+        }
+        Command::Repl(repl) => {
+            let year = repl.year.unwrap_or_else(registry::latest_year);
+            if select(year, None).is_none() {
+                return ExitCode::FAILURE;
+            }
+            if repl::run(year) { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+        }
+        Command::Serve(serve) => {
+            if serve::run(&serve) { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+        }
+        Command::Tui(tui) => {
+            let year = tui.year.unwrap_or_else(registry::latest_year);
+            if select(year, None).is_none() {
+                return ExitCode::FAILURE;
+            }
+            if tui::run(&tui, year) { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+        }
+        Command::History(history) => {
+            let year = history.year.unwrap_or_else(registry::latest_year);
+            let days = match select(year, history.days.clone()) {
+                Some(days) => days,
+                None => return ExitCode::FAILURE,
+            };
+            if history::run(&history, year, &days) { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+        }
+        Command::Crosscheck(run) => {
+            let year = run.year.unwrap_or_else(registry::latest_year);
+            let days = match select(year, Some(run.days.unwrap_or_else(|| crosscheck::days(year)))) {
+                Some(days) => days,
+                None => return ExitCode::FAILURE,
+            };
+            let resolver = Resolver::from_env(run.input);
+            if crosscheck::run(year, &days, &resolver, &runner::parts(run.part), run.timeout) { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+        }
+        Command::Run(run) => {
+            let year = run.year.unwrap_or_else(registry::latest_year);
+            let days = match select(year, run.days).filter(|days| has_variant(year, days, run.variant.as_deref())) {
+                Some(days) => days,
+                None => return ExitCode::FAILURE,
+            };
+            if run.explain.as_ref().is_some_and(|explain| !explain::install(explain, &days)) {
+                return ExitCode::FAILURE;
+            }
+
+            let answers = if run.verify {
+                match Answers::load(&run.answers.unwrap_or_else(|| answers::default_path(year))) {
+                    // Another input than the puzzle input has its own answers, named after its file.
+                    Ok(answers) => Some(match run.input.as_deref() {
+                        Some(input) => answers.input(&answers::input_name(input)),
+                        None => answers,
+                    }),
+                    Err(message) => {
+                        eprintln!("{}", message);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            } else {
+                None
+            };
+
+            // Timings of other inputs than the puzzle input, or of explained runs, aren't comparable.
+            let record = run.input.is_none() && !run.no_history && run.explain.is_none();
+            let resolver = Resolver::from_env(run.input);
+            let parts = runner::parts(run.part);
+            let now = Instant::now();
+            let mut results = vec![];
+            let options = runner::Options {
+                variant: run.variant.as_deref().unwrap_or(registry::DEFAULT_VARIANT),
+                parts: &parts,
+                timeout: run.timeout,
+            };
+            runner::run_days(year, &days, &resolver, options, run.jobs, |result| {
+                if run.format == Format::Table {
+                    report::print_table(&result, answers.as_ref());
+                }
+                results.push(result);
+            });
+            match run.format {
+                Format::Table => {
+                    // The wall-clock times of the steps added up, which exceed the total when run on several jobs.
+                    let summed: Duration = results.iter().map(|result| result.elapsed()).sum();
+                    println!("total: {:.2?} (summed: {:.2?})", now.elapsed(), summed);
+                }
+                Format::Json => println!("{}", report::json(&report::records(&results, &parts))),
+                Format::Csv => print!("{}", report::csv(&report::records(&results, &parts))),
+            }
+
+            if record {
+                let entries = history::entries(history::timestamp(), &history::Build::current(run.jobs), options.variant, &results);
+                let path = run.history.as_deref().unwrap_or(history::DEFAULT_PATH);
+                if let Err(message) = history::append(std::path::Path::new(path), &entries) {
+                    eprintln!("{}", message);
+                }
+            }
+
+            let mut failed = results.iter().any(|result| result.failed());
+            if let Some(answers) = &answers {
+                let (passed, mismatched, unknown) = report::verify(&results, answers);
+                // Keep machine readable output on stdout clean.
+                let summary = format!("verify: {} passed, {} failed, {} unknown", passed, mismatched, unknown);
+                if run.format == Format::Table { println!("{}", summary) } else { eprintln!("{}", summary) }
+                failed |= mismatched > 0;
+            }
+            if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+        }
+    }
+}
+
+/// Returns the requested days of `year`, or every registered day of it. Reports unknown days and returns `None`
+/// if there are any.
+fn select(year: u16, days: Option<Vec<u8>>) -> Option<Vec<u8>> {
+    let available = registry::days(year);
+    if available.is_empty() {
+        let years: Vec<String> = registry::years().iter().map(|year| year.to_string()).collect();
+        eprintln!("No solvers for {} (available: {})", year, years.join(", "));
+        return None;
+    }
+    let days = days.unwrap_or_else(|| available.clone());
+    let unknown: Vec<String> = days.iter().filter(|day| !available.contains(day)).map(|day| day.to_string()).collect();
+    if !unknown.is_empty() {
+        let available: Vec<String> = available.iter().map(|day| day.to_string()).collect();
+        eprintln!("Unknown day(s) of {}: {} (available: {})", year, unknown.join(", "), available.join(", "));
+        return None;
+    }
+    Some(days)
+}
+
+/// Whether every one of `days` has the solver named `variant`, if given. Reports the days without it.
+fn has_variant(year: u16, days: &[u8], variant: Option<&str>) -> bool {
+    let variant = match variant {
+        Some(variant) => variant,
+        None => return true,
+    };
+    let mut found = true;
+    for &day in days {
+        let puzzle = registry::find(year, day).expect("selected day");
+        if puzzle.variant(variant).is_none() {
+            eprintln!("Unknown variant of day {:02}: '{}' (available: {})", day, variant, puzzle.variant_names().join(", "));
+            found = false;
+        }
+    }
+    found
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::cli::Bench;
+use crate::input::Resolver;
+use crate::{registry, runner};
+
+/// Summary statistics over the timed iterations of one step.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Stats {
+    pub(crate) min: Duration,
+    pub(crate) median: Duration,
+    pub(crate) mean: Duration,
+    pub(crate) stddev: Duration,
+}
+
+impl Stats {
+    /// Computes the statistics of `samples`, which must not be empty.
+    pub(crate) fn new(samples: &[Duration]) -> Stats {
+        let mut nanos: Vec<f64> = samples.iter().map(|sample| sample.as_nanos() as f64).collect();
+        nanos.sort_unstable_by(f64::total_cmp);
+        let n = nanos.len();
+        let median = if n.is_multiple_of(2) { (nanos[n / 2 - 1] + nanos[n / 2]) / 2.0 } else { nanos[n / 2] };
+        let mean = nanos.iter().sum::<f64>() / n as f64;
+        let variance = nanos.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+        Stats {
+            min: Duration::from_nanos(nanos[0] as u64),
+            median: Duration::from_nanos(median as u64),
+            mean: Duration::from_nanos(mean as u64),
+            stddev: Duration::from_nanos(variance.sqrt() as u64),
+        }
+    }
+}
+
+/// Runs `f` `warmup` times untimed, then `iterations` times timed.
+///
+/// Stops at the first error, which is returned instead of the statistics.
+pub(crate) fn measure<T, E>(warmup: usize, iterations: usize, mut f: impl FnMut() -> Result<T, E>) -> Result<Stats, E> {
+    for _ in 0..warmup {
+        f()?;
+    }
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let now = Instant::now();
+        f()?;
+        samples.push(now.elapsed());
+    }
+    Ok(Stats::new(&samples))
+}
+
+/// Results keyed by year, day and step (`parse`, `pt1` or `pt2`).
+pub(crate) type Baseline = BTreeMap<(u16, u8, String), Stats>;
+
+const BASELINE_HEADER: &str = "year\tday\tstep\tmin_ns\tmedian_ns\tmean_ns\tstddev_ns";
+
+/// Formats `baseline` as tab separated lines, with a header.
+pub(crate) fn format_baseline(baseline: &Baseline) -> String {
+    let mut out = String::from(BASELINE_HEADER);
+    out.push('\n');
+    for ((year, day, step), stats) in baseline {
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            year,
+            day,
+            step,
+            stats.min.as_nanos(),
+            stats.median.as_nanos(),
+            stats.mean.as_nanos(),
+            stats.stddev.as_nanos(),
+        ));
+    }
+    out
+}
+
+/// Parses a baseline written by [`format_baseline`].
+pub(crate) fn parse_baseline(inp: &str) -> Result<Baseline, String> {
+    let mut baseline = Baseline::new();
+    for (number, line) in inp.lines().enumerate() {
+        if line.trim().is_empty() || line == BASELINE_HEADER {
+            continue;
+        }
+        let malformed = || format!("line {}: malformed baseline entry '{}'", number + 1, line);
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 7 {
+            return Err(malformed());
+        }
+        let year = fields[0].parse::<u16>().map_err(|_| malformed())?;
+        let day = fields[1].parse::<u8>().map_err(|_| malformed())?;
+        let mut nanos = fields[3..].iter().map(|field| field.parse::<u64>().map(Duration::from_nanos));
+        let mut next = || nanos.next().unwrap().map_err(|_| malformed());
+        let stats = Stats { min: next()?, median: next()?, mean: next()?, stddev: next()? };
+        baseline.insert((year, day, fields[2].to_string()), stats);
+    }
+    Ok(baseline)
+}
+
+/// Returns the change of `current` against `baseline`, in percent.
+fn change(baseline: Duration, current: Duration) -> f64 {
+    if baseline.is_zero() {
+        return 0.0;
+    }
+    (current.as_nanos() as f64 - baseline.as_nanos() as f64) / baseline.as_nanos() as f64 * 100.0
+}
+
+/// Benchmarks `days` of `year`, printing a table of the results. Returns false if any step failed or regressed.
+pub(crate) fn run(bench: &Bench, year: u16, days: &[u8]) -> bool {
+    let baseline = match &bench.baseline {
+        Some(path) => match std::fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|inp| parse_baseline(&inp)) {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                eprintln!("Failed to read baseline '{}': {}", path, e);
+                return false;
+            }
+        },
+        None => None,
+    };
+
+    let resolver = Resolver::from_env(bench.run.input.clone());
+    let variant = bench.run.variant.as_deref().unwrap_or(registry::DEFAULT_VARIANT);
+    let mut results = Baseline::new();
+    let mut ok = true;
+    println!(
+        "{:14} {:>10} {:>10} {:>10} {:>10}{}",
+        "",
+        "min",
+        "median",
+        "mean",
+        "stddev",
+        if baseline.is_some() { "     change" } else { "" }
+    );
+    let (warmup, iterations) = (bench.warmup, bench.iterations);
+    // Every step is measured on its own solver thread, which may take the timeout for each of its runs.
+    let timeout = bench.run.timeout.map(|timeout| timeout.saturating_mul((warmup + iterations) as u32));
+    for &day in days {
+        let solver = registry::find(year, day).and_then(|puzzle| puzzle.variant(variant)).unwrap();
+        let inp = match resolver.resolve(year, day) {
+            Ok(inp) => inp,
+            Err(message) => {
+                eprintln!("{}", message);
+                ok = false;
+                continue;
+            }
+        };
+
+        // Prints and records the result of one step, returning false if it regressed.
+        let mut report = |step: &str, stats: Stats| -> bool {
+            let mut regressed = false;
+            let mut line = format!(
+                "day{:02} - {:<5}: {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?}",
+                day, step, stats.min, stats.median, stats.mean, stats.stddev
+            );
+            if let Some(previous) = baseline.as_ref().and_then(|baseline| baseline.get(&(year, day, step.to_string()))) {
+                let change = change(previous.median, stats.median);
+                line.push_str(&format!(" {:>+9.1}%", change));
+                if change > bench.threshold {
+                    line.push_str(" REGRESSION");
+                    regressed = true;
+                }
+            }
+            println!("{}", line);
+            results.insert((year, day, step.to_string()), stats);
+            !regressed
+        };
+
+        let parse = runner::run_step(year, day, None, timeout, move || {
+            let stats = measure(warmup, iterations, || solver.parse(&inp).map(|_| ()))?;
+            Ok((stats, Arc::new(solver.parse(&inp)?)))
+        });
+        let parsed = match parse.result {
+            Ok((stats, parsed)) => {
+                ok &= report("parse", stats);
+                parsed
+            }
+            Err(e) => {
+                println!("day{:02} - parse: {} {}", day, e.label(), e);
+                ok = false;
+                continue;
+            }
+        };
+        for part in [1, 2] {
+            if bench.run.part.map(|p| p != part).unwrap_or(false) {
+                continue;
+            }
+            let step = format!("pt{}", part);
+            let parsed = parsed.clone();
+            let measured = runner::run_step(year, day, Some(part), timeout, move || {
+                measure(warmup, iterations, || if part == 1 { parsed.pt1() } else { parsed.pt2() })
+            });
+            match measured.result {
+                Ok(stats) => ok &= report(&step, stats),
+                Err(e) => {
+                    println!("day{:02} - {:<5}: {} {}", day, step, e.label(), e);
+                    ok = false;
+                }
+            }
+        }
+    }
+
+    if let Some(path) = &bench.save {
+        if let Err(e) = std::fs::write(path, format_baseline(&results)) {
+            eprintln!("Failed to write baseline '{}': {}", path, e);
+            ok = false;
+        }
+    }
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn micros(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|v| Duration::from_micros(*v)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(
+            Stats::new(&micros(&[4, 2, 9, 5, 5, 4, 7, 4])),
+            Stats {
+                min: Duration::from_micros(2),
+                median: Duration::from_micros(4) + Duration::from_nanos(500),
+                mean: Duration::from_micros(5),
+                stddev: Duration::from_micros(2),
+            }
+        );
+        assert_eq!(Stats::new(&micros(&[3, 1, 2])).median, Duration::from_micros(2));
+    }
+
+    #[test]
+    fn test_measure() {
+        let mut calls = 0;
+        let stats = measure(2, 5, || -> Result<(), ()> {
+            calls += 1;
+            Ok(())
+        });
+        assert!(stats.is_ok());
+        assert_eq!(calls, 7);
+
+        assert_eq!(measure(0, 5, || Err::<(), _>("failed")), Err("failed"));
+    }
+
+    #[test]
+    fn test_baseline_roundtrip() {
+        let mut baseline = Baseline::new();
+        baseline.insert((2021, 4, "parse".to_string()), Stats::new(&micros(&[10, 12])));
+        baseline.insert((2021, 12, "pt2".to_string()), Stats::new(&micros(&[900, 1100, 1000])));
+        assert_eq!(parse_baseline(&format_baseline(&baseline)), Ok(baseline));
+    }
+
+    #[test]
+    fn test_parse_baseline_malformed() {
+        assert_eq!(parse_baseline("2021\t4\tpt1\t1\t2\t3"), Err("line 1: malformed baseline entry '2021\t4\tpt1\t1\t2\t3'".to_string()));
+        assert!(parse_baseline("2021\t4\tpt1\t1\t2\tx\t4").is_err());
+        // Baselines from before years were recorded have no year column.
+        assert!(parse_baseline("4\tpt1\t1\t2\t3\t4").is_err());
+    }
+
+    #[test]
+    fn test_change() {
+        assert_eq!(change(Duration::from_micros(100), Duration::from_micros(125)), 25.0);
+        assert_eq!(change(Duration::from_micros(100), Duration::from_micros(50)), -50.0);
+        assert_eq!(change(Duration::ZERO, Duration::from_micros(50)), 0.0);
+    }
+}
@@ -0,0 +1,1078 @@
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use crate::report::Format;
+
+pub(crate) const USAGE: &str = "Usage:
+    adventofcode-2021 [run] [--year <YYYY>] [DAYS] [--part <1|2>] [--input <path>] [--variant <name>]
+                            [--format <table|json|csv>] [--jobs <n>] [--timeout <seconds>] [--verify]
+                            [--answers <path>] [--history <path>] [--no-history] [--explain[=DAYS]]
+    adventofcode-2021 bench [--year <YYYY>] [DAYS] [--part <1|2>] [--input <path>] [--variant <name>]
+                            [--warmup <n>] [--iterations <n>] [--save <path>] [--baseline <path>]
+                            [--threshold <percent>] [--timeout <seconds>]
+    adventofcode-2021 crosscheck [--year <YYYY>] [DAYS] [--part <1|2>] [--input <path>] [--timeout <seconds>]
+    adventofcode-2021 watch [--year <YYYY>] <DAY> [--part <1|2>] [--input <path>] [--variant <name>]
+                            [--examples] [--timeout <seconds>] [--explain[=DAYS]]
+    adventofcode-2021 repl [--year <YYYY>]
+    adventofcode-2021 serve [--address <host:port>] [--max-body <bytes>] [--jobs <n>] [--timeout <seconds>]
+    adventofcode-2021 tui [--year <YYYY>] [--jobs <n>] [--timeout <seconds>] [--history <path>]
+    adventofcode-2021 history [--year <YYYY>] [DAYS] [--window <n>] [--factor <x>] [--profile <name>]
+                              [--jobs <n>] [--history <path>]
+    adventofcode-2021 fetch [--year <YYYY>] <DAY> [--base-url <url>]
+    adventofcode-2021 submit [--year <YYYY>] <DAY> <PART> [--answer <answer>] [--input <path>]
+                             [--ledger <path>] [--base-url <url>]
+    adventofcode-2021 new [--year <YYYY>] <DAY> [--title <title>]
+    adventofcode-2021 --list
+
+`--year` selects the event, and defaults to the latest year with solvers.
+
+DAYS is a single day (`4`), an inclusive range (`3..7`) or a comma separated
+list of both (`1,3..5`), given as is or with `--day`. All days of the year are
+run when DAYS is omitted.
+
+Inputs are read from `--input <path>` (`-` for stdin, single day only), then
+`$AOC_INPUT_DIR/YYYY/dayNN.txt` or `inputs/YYYY/dayNN.txt`, then the embedded
+inputs.
+
+`--variant` selects an alternative solver, such as `median` for day 7, as
+shown by `--list`. `crosscheck` runs every variant, of the days with more than
+one when DAYS is omitted, and fails when their answers disagree.
+
+`--format json` and `--format csv` print one record per part with its year,
+day, part, answer, duration_ns, allocations, allocated_bytes and peak_bytes
+(when built with the `count-allocations` feature), status (`ok`, `error`,
+`panic` or `timeout`) and error message.
+
+`--jobs` runs up to n days, and the parts of each day once it is parsed, in
+parallel (default 1). Results are still printed in day order, followed by the
+wall-clock total and the summed time of all days.
+
+A panicking solver is reported as PANIC, and with `--timeout` a step that
+takes longer than the given seconds as TIMEOUT, after which the remaining days
+are still run.
+
+`--explain` (or `-v`) writes how the selected days are solved to stderr, such
+as which board wins at which draw on day 4, or only of the days in
+`--explain=DAYS`. The timings of an explained run include writing the
+explanation, so they are left out of the history. Release builds leave the
+events out unless they are built without the default `quiet-release` feature,
+as in `cargo build --release --no-default-features --features embedded-inputs`.
+
+`--verify` checks each answer against the known answers in `answers/YYYY.toml` (or
+`--answers <path>`), reporting PASS or FAIL per part and failing on a mismatch.
+With `--input <name>.txt` the answers in its `[dayNN.<name>]` table are checked.
+
+`fetch` downloads the input of a day into the inputs directory, unless it is
+already there. It authenticates with the session cookie in `$AOC_SESSION` or
+`~/.config/adventofcode/session`, against `--base-url` or `$AOC_BASE_URL`
+(default https://adventofcode.com).
+
+`submit` solves a part, or takes `--answer`, and submits the answer. Outcomes
+are kept in `submissions.tsv` (or `--ledger <path>`), and answers that are
+known to be wrong, too high or too low are refused without submitting.
+
+`new` creates `src/dayNN.rs` from a template, an empty `src/inputNN.txt` and
+registers the module in `src/lib.rs`. Days of other years than 2021 are named
+`yYYYY_dayNN`. It never overwrites an existing day.
+
+`bench` times every part `--iterations` times (default 20) after `--warmup`
+untimed runs (default 3). `--save` writes the results to a baseline file, and
+`--baseline` compares against one, failing when a median is more than
+`--threshold` percent (default 10) slower. A step that panics is reported as
+PANIC, and with `--timeout` one that takes longer than the given seconds per
+run as TIMEOUT, after which the remaining steps are still benchmarked.
+
+`watch` solves a day again whenever its input file changes, clearing the
+screen and showing the new answers and timings. With `--examples` it also
+solves the files in `tests/fixtures/YYYY/dayNN/*.txt`, whenever any of them
+changes, checking their answers in the `.toml` file next to each.
+
+`repl` loads the input of a day and applies operations of its domain to it,
+such as `fold` on the manual of day 13, `simulate 10` on the octopuses of day
+11 or `polymerize 5` on the device of day 14, showing the resulting state. Type
+`help` in it for the commands, which are kept in the history of later sessions.
+
+`serve` answers `POST /<year>/day/<day>/part/<1|2>` requests on `--address`
+(default 127.0.0.1:2021) with the answer to the puzzle input in the body, as
+JSON with its answer, parse_ns and duration_ns. Inputs over `--max-body` bytes
+(default 1048576) are refused, as are requests beyond `--jobs` (default 4)
+at once, and a request may take up to `--timeout` seconds (default 30). A
+solver that takes longer counts against `--jobs` until it finishes. Failures
+are reported as an `error` object with their kind and message, and the line
+and column of inputs that can't be parsed.
+
+`tui` runs every day of the year in a full-screen table with the status,
+answers and recent timings of each day, and keys to run a day again or look at
+the visualization of its input. It runs the days on `--jobs` threads as `run`
+does.
+
+Every run appends the timings of its successful steps, with the commit, build
+profile, features, `--jobs` and machine, to `history.tsv` in the working
+directory (or `--history <path>`), unless it is given `--input` or
+`--no-history`. `history` shows the latest timing of each step next to the
+median of up to `--window` runs before it (default 10) and a trend, for the runs
+of this machine, build profile (or `--profile`) and features with `--jobs`
+threads (default 1). It fails when a step got more than `--factor` times
+(default 1.5) slower.";
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Command {
+    Run(Run),
+    Bench(Bench),
+    /// Runs every variant of the selected days, comparing their answers.
+    Crosscheck(Run),
+    /// Solves a day again whenever its input or examples change.
+    Watch(Watch),
+    History(History),
+    Repl(Repl),
+    Serve(Serve),
+    Tui(Tui),
+    Fetch(Fetch),
+    Submit(Submit),
+    New(New),
+    List,
+    Help,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Run {
+    /// The requested year, or `None` for the latest year.
+    pub(crate) year: Option<u16>,
+    /// The requested days in ascending order, or `None` for every registered day.
+    pub(crate) days: Option<Vec<u8>>,
+    /// The requested part, or `None` for both parts.
+    pub(crate) part: Option<u8>,
+    /// An explicit input path, `-` meaning stdin.
+    pub(crate) input: Option<String>,
+    /// The solver to run, or `None` for the default solver of each day.
+    pub(crate) variant: Option<String>,
+    pub(crate) format: Format,
+    /// The number of days to run in parallel.
+    pub(crate) jobs: usize,
+    /// The longest parsing or a part may take, or `None` to wait for it however long it takes.
+    pub(crate) timeout: Option<Duration>,
+    /// Whether to check the answers against the known answers.
+    pub(crate) verify: bool,
+    /// The known answers file, defaults to `answers/YYYY.toml`.
+    pub(crate) answers: Option<String>,
+    /// The history file to record the timings in, defaults to `history.tsv` in the working directory.
+    pub(crate) history: Option<String>,
+    /// Whether to leave the timings out of the history.
+    pub(crate) no_history: bool,
+    /// The days to explain the solving of, empty for every selected day, or `None` to explain none.
+    pub(crate) explain: Option<Vec<u8>>,
+}
+
+impl Default for Run {
+    fn default() -> Run {
+        Run {
+            year: None,
+            days: None,
+            part: None,
+            input: None,
+            variant: None,
+            format: Format::default(),
+            jobs: 1,
+            timeout: None,
+            verify: false,
+            answers: None,
+            history: None,
+            no_history: false,
+            explain: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Bench {
+    pub(crate) run: Run,
+    pub(crate) warmup: usize,
+    pub(crate) iterations: usize,
+    pub(crate) save: Option<String>,
+    pub(crate) baseline: Option<String>,
+    /// Allowed slowdown against the baseline, in percent.
+    pub(crate) threshold: f64,
+}
+
+impl Default for Bench {
+    fn default() -> Bench {
+        Bench {
+            run: Run::default(),
+            warmup: 3,
+            iterations: 20,
+            save: None,
+            baseline: None,
+            threshold: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Watch {
+    /// The day to watch, with its input, part and variant.
+    pub(crate) run: Run,
+    /// Whether to solve the examples of the day as well.
+    pub(crate) examples: bool,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct Repl {
+    pub(crate) year: Option<u16>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Serve {
+    /// The host and port to listen on.
+    pub(crate) address: String,
+    /// The largest input accepted, in bytes.
+    pub(crate) max_body: usize,
+    /// The number of requests answered at once.
+    pub(crate) jobs: usize,
+    /// The longest parsing or a part may take.
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl Default for Serve {
+    fn default() -> Serve {
+        Serve { address: "127.0.0.1:2021".to_string(), max_body: 1024 * 1024, jobs: 4, timeout: Some(Duration::from_secs(30)) }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Tui {
+    pub(crate) year: Option<u16>,
+    /// The number of threads to run the days on.
+    pub(crate) jobs: usize,
+    /// The longest parsing or a part may take, or `None` to wait for it however long it takes.
+    pub(crate) timeout: Option<Duration>,
+    /// The history file to read and record the timings in, defaults to `history.tsv` in the working directory.
+    pub(crate) history: Option<String>,
+}
+
+impl Default for Tui {
+    fn default() -> Tui {
+        Tui { year: None, jobs: 1, timeout: None, history: None }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct History {
+    pub(crate) year: Option<u16>,
+    /// The requested days in ascending order, or `None` for every registered day.
+    pub(crate) days: Option<Vec<u8>>,
+    /// The build profile of the runs to show, or `None` for the profile of this binary.
+    pub(crate) profile: Option<String>,
+    /// The number of threads of the runs to show.
+    pub(crate) jobs: usize,
+    /// The number of runs before the latest one to take the median of.
+    pub(crate) window: usize,
+    /// How many times slower than the median the latest run may be.
+    pub(crate) factor: f64,
+    /// The history file, defaults to `history.tsv` in the working directory.
+    pub(crate) path: Option<String>,
+}
+
+impl Default for History {
+    fn default() -> History {
+        History { year: None, days: None, profile: None, jobs: 1, window: 10, factor: 1.5, path: None }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Fetch {
+    pub(crate) year: Option<u16>,
+    pub(crate) day: u8,
+    pub(crate) base_url: Option<String>,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct Submit {
+    pub(crate) year: Option<u16>,
+    pub(crate) day: u8,
+    pub(crate) part: u8,
+    /// The answer to submit instead of solving the part.
+    pub(crate) answer: Option<String>,
+    pub(crate) input: Option<String>,
+    pub(crate) ledger: Option<String>,
+    pub(crate) base_url: Option<String>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct New {
+    pub(crate) year: Option<u16>,
+    pub(crate) day: u8,
+    pub(crate) title: Option<String>,
+}
+
+pub(crate) fn parse<I: IntoIterator<Item=String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    match args.peek().map(|a| a.as_str()) {
+        Some("run") => {
+            args.next();
+        }
+        Some("bench") => {
+            args.next();
+            return parse_bench(args);
+        }
+        Some("crosscheck") => {
+            args.next();
+            return parse_crosscheck(args);
+        }
+        Some("watch") => {
+            args.next();
+            return parse_watch(args);
+        }
+        Some("repl") => {
+            args.next();
+            return parse_repl(args);
+        }
+        Some("serve") => {
+            args.next();
+            return parse_serve(args);
+        }
+        Some("tui") => {
+            args.next();
+            return parse_tui(args);
+        }
+        Some("history") => {
+            args.next();
+            return parse_history(args);
+        }
+        Some("fetch") => {
+            args.next();
+            return parse_fetch(args);
+        }
+        Some("submit") => {
+            args.next();
+            return parse_submit(args);
+        }
+        Some("new") => {
+            args.next();
+            return parse_new(args);
+        }
+        _ => {}
+    }
+
+    let mut run = Run::default();
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--list" | "list" => return Ok(Command::List),
+            "--help" | "-h" | "help" => return Ok(Command::Help),
+            "--format" => run.format = parse_format(&value(name, inline, &mut args)?)?,
+            "--jobs" | "-j" => {
+                run.jobs = parse_number(name, &value(name, inline, &mut args)?)?;
+                if run.jobs == 0 {
+                    return Err("--jobs must be at least 1".to_string());
+                }
+            }
+            "--timeout" => run.timeout = Some(parse_timeout(&value(name, inline, &mut args)?)?),
+            "--verify" => run.verify = true,
+            "--answers" => run.answers = Some(value(name, inline, &mut args)?),
+            "--history" => run.history = Some(value(name, inline, &mut args)?),
+            "--no-history" => run.no_history = true,
+            "--explain" | "-v" => run.explain = Some(parse_explain(inline)?),
+            _ => parse_run_arg(&mut run, arg, &mut args)?,
+        }
+    }
+    validate_run(&run)?;
+    Ok(Command::Run(run))
+}
+
+fn parse_bench<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut bench = Bench::default();
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--warmup" => bench.warmup = parse_number(name, &value(name, inline, &mut args)?)?,
+            "--iterations" => bench.iterations = parse_number(name, &value(name, inline, &mut args)?)?,
+            "--save" => bench.save = Some(value(name, inline, &mut args)?),
+            "--baseline" => bench.baseline = Some(value(name, inline, &mut args)?),
+            "--threshold" => bench.threshold = parse_number(name, &value(name, inline, &mut args)?)?,
+            "--timeout" => bench.run.timeout = Some(parse_timeout(&value(name, inline, &mut args)?)?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ => parse_run_arg(&mut bench.run, arg, &mut args)?,
+        }
+    }
+    if bench.iterations == 0 {
+        return Err("--iterations must be at least 1".to_string());
+    }
+    if bench.threshold.is_nan() || bench.threshold < 0.0 {
+        return Err(format!("--threshold must be a percentage of at least 0, not {}", bench.threshold));
+    }
+    validate_run(&bench.run)?;
+    Ok(Command::Bench(bench))
+}
+
+fn parse_crosscheck<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut run = Run::default();
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--variant" => return Err("crosscheck runs every variant, --variant can't be selected".to_string()),
+            "--timeout" => run.timeout = Some(parse_timeout(&value(name, inline, &mut args)?)?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ => parse_run_arg(&mut run, arg, &mut args)?,
+        }
+    }
+    validate_run(&run)?;
+    Ok(Command::Crosscheck(run))
+}
+
+fn parse_watch<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut watch = Watch::default();
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--examples" | "-e" => watch.examples = true,
+            "--timeout" => watch.run.timeout = Some(parse_timeout(&value(name, inline, &mut args)?)?),
+            "--explain" | "-v" => watch.run.explain = Some(parse_explain(inline)?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ => parse_run_arg(&mut watch.run, arg, &mut args)?,
+        }
+    }
+    match &watch.run.days {
+        Some(days) if days.len() == 1 => {}
+        Some(_) => return Err("watch takes a single day".to_string()),
+        None => return Err("Missing day".to_string()),
+    }
+    if watch.run.input.as_deref() == Some("-") {
+        return Err("watch can't read its input from stdin".to_string());
+    }
+    Ok(Command::Watch(watch))
+}
+
+fn parse_repl<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut repl = Repl::default();
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--year" | "-y" => repl.year = Some(parse_year(&value(name, inline, &mut args)?)?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: '{}'", arg)),
+            _ => return Err(format!("Unexpected argument: '{}'", arg)),
+        }
+    }
+    Ok(Command::Repl(repl))
+}
+
+fn parse_serve<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut serve = Serve::default();
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--address" => serve.address = value(name, inline, &mut args)?,
+            "--max-body" => serve.max_body = parse_number(name, &value(name, inline, &mut args)?)?,
+            "--jobs" | "-j" => {
+                serve.jobs = parse_number(name, &value(name, inline, &mut args)?)?;
+                if serve.jobs == 0 {
+                    return Err("--jobs must be at least 1".to_string());
+                }
+            }
+            "--timeout" => serve.timeout = Some(parse_timeout(&value(name, inline, &mut args)?)?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: '{}'", arg)),
+            _ => return Err(format!("Unexpected argument: '{}'", arg)),
+        }
+    }
+    Ok(Command::Serve(serve))
+}
+
+fn parse_tui<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut tui = Tui::default();
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--year" | "-y" => tui.year = Some(parse_year(&value(name, inline, &mut args)?)?),
+            "--jobs" | "-j" => {
+                tui.jobs = parse_number(name, &value(name, inline, &mut args)?)?;
+                if tui.jobs == 0 {
+                    return Err("--jobs must be at least 1".to_string());
+                }
+            }
+            "--timeout" => tui.timeout = Some(parse_timeout(&value(name, inline, &mut args)?)?),
+            "--history" => tui.history = Some(value(name, inline, &mut args)?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: '{}'", arg)),
+            _ => return Err(format!("Unexpected argument: '{}'", arg)),
+        }
+    }
+    Ok(Command::Tui(tui))
+}
+
+fn parse_history<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut history = History::default();
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        let days = match name {
+            "--year" | "-y" => {
+                history.year = Some(parse_year(&value(name, inline, &mut args)?)?);
+                continue;
+            }
+            "--day" | "-d" => value(name, inline, &mut args)?,
+            "--window" => {
+                history.window = parse_number(name, &value(name, inline, &mut args)?)?;
+                continue;
+            }
+            "--factor" => {
+                history.factor = parse_number(name, &value(name, inline, &mut args)?)?;
+                continue;
+            }
+            "--profile" => {
+                history.profile = Some(value(name, inline, &mut args)?);
+                continue;
+            }
+            "--jobs" | "-j" => {
+                history.jobs = parse_number(name, &value(name, inline, &mut args)?)?;
+                if history.jobs == 0 {
+                    return Err("--jobs must be at least 1".to_string());
+                }
+                continue;
+            }
+            "--history" => {
+                history.path = Some(value(name, inline, &mut args)?);
+                continue;
+            }
+            "--help" | "-h" => return Ok(Command::Help),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: '{}'", arg)),
+            _ => arg,
+        };
+        if history.days.is_some() {
+            return Err(format!("Unexpected argument: '{}'", days));
+        }
+        history.days = Some(parse_days(&days)?);
+    }
+    if history.window == 0 {
+        return Err("--window must be at least 1".to_string());
+    }
+    if !(history.factor > 0.0 && history.factor.is_finite()) {
+        return Err(format!("Invalid factor: '{}', expected a positive number", history.factor));
+    }
+    Ok(Command::History(history))
+}
+
+fn parse_fetch<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut year = None;
+    let mut day = None;
+    let mut base_url = None;
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--year" | "-y" => year = Some(parse_year(&value(name, inline, &mut args)?)?),
+            "--base-url" => base_url = Some(value(name, inline, &mut args)?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: '{}'", arg)),
+            _ if day.is_some() => return Err(format!("Unexpected argument: '{}'", arg)),
+            _ => day = Some(parse_puzzle_day(&arg)?),
+        }
+    }
+    let day = day.ok_or_else(|| "Missing day".to_string())?;
+    Ok(Command::Fetch(Fetch { year, day, base_url }))
+}
+
+fn parse_submit<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut submit = Submit::default();
+    let mut positional = vec![];
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--year" | "-y" => submit.year = Some(parse_year(&value(name, inline, &mut args)?)?),
+            "--answer" | "-a" => submit.answer = Some(value(name, inline, &mut args)?),
+            "--input" | "-i" => submit.input = Some(value(name, inline, &mut args)?),
+            "--ledger" => submit.ledger = Some(value(name, inline, &mut args)?),
+            "--base-url" => submit.base_url = Some(value(name, inline, &mut args)?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: '{}'", arg)),
+            _ => positional.push(arg),
+        }
+    }
+    match positional.as_slice() {
+        [day, part] => {
+            submit.day = parse_puzzle_day(day)?;
+            submit.part = parse_part(part)?;
+        }
+        [] | [_] => return Err("Missing day or part".to_string()),
+        [_, _, arg, ..] => return Err(format!("Unexpected argument: '{}'", arg)),
+    }
+    Ok(Command::Submit(submit))
+}
+
+fn parse_new<I: Iterator<Item=String>>(mut args: I) -> Result<Command, String> {
+    let mut year = None;
+    let mut day = None;
+    let mut title = None;
+    while let Some(arg) = args.next() {
+        let (name, inline) = split_option(&arg);
+        match name {
+            "--year" | "-y" => year = Some(parse_year(&value(name, inline, &mut args)?)?),
+            "--title" => title = Some(value(name, inline, &mut args)?),
+            "--help" | "-h" => return Ok(Command::Help),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: '{}'", arg)),
+            _ if day.is_some() => return Err(format!("Unexpected argument: '{}'", arg)),
+            _ => day = Some(parse_puzzle_day(&arg)?),
+        }
+    }
+    let day = day.ok_or_else(|| "Missing day".to_string())?;
+    Ok(Command::New(New { year, day, title }))
+}
+
+/// Parses the day of a puzzle which may not have a solver yet.
+fn parse_puzzle_day(value: &str) -> Result<u8, String> {
+    match parse_day(value)? {
+        day @ 1..=25 => Ok(day),
+        _ => Err(format!("Invalid day: '{}', expected 1 to 25", value)),
+    }
+}
+
+/// Parses the year, day, part and input selection shared by the commands that run solvers.
+fn parse_run_arg<I: Iterator<Item=String>>(run: &mut Run, arg: String, args: &mut I) -> Result<(), String> {
+    let (name, inline) = split_option(&arg);
+    let days = match name {
+        "--year" | "-y" => {
+            run.year = Some(parse_year(&value(name, inline, args)?)?);
+            return Ok(());
+        }
+        "--day" | "-d" => value(name, inline, args)?,
+        "--part" | "-p" => {
+            run.part = Some(parse_part(&value(name, inline, args)?)?);
+            return Ok(());
+        }
+        "--input" | "-i" => {
+            run.input = Some(value(name, inline, args)?);
+            return Ok(());
+        }
+        "--variant" => {
+            run.variant = Some(value(name, inline, args)?);
+            return Ok(());
+        }
+        _ if arg.starts_with('-') => return Err(format!("Unknown option: '{}'", arg)),
+        _ => arg,
+    };
+    if run.days.is_some() {
+        return Err(format!("Unexpected argument: '{}'", days));
+    }
+    run.days = Some(parse_days(&days)?);
+    Ok(())
+}
+
+/// Parses the days of `--explain=DAYS`, which only takes them inline to tell them apart from the selected days.
+fn parse_explain(inline: Option<&str>) -> Result<Vec<u8>, String> {
+    inline.map(parse_days).unwrap_or_else(|| Ok(vec![]))
+}
+
+fn validate_run(run: &Run) -> Result<(), String> {
+    if run.input.is_some() && run.days.as_ref().map(|days| days.len() != 1).unwrap_or(true) {
+        return Err("--input requires exactly one day".to_string());
+    }
+    if run.verify && run.input.as_deref() == Some("-") {
+        return Err("--verify can't tell the answers of stdin, give the input as a file".to_string());
+    }
+    Ok(())
+}
+
+/// Splits `--name=value` into its name and value.
+fn split_option(arg: &str) -> (&str, Option<&str>) {
+    match arg.split_once('=') {
+        Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+        _ => (arg, None),
+    }
+}
+
+/// Returns the value of option `name`, given either inline or as the next argument.
+fn value<I: Iterator<Item=String>>(name: &str, inline: Option<&str>, args: &mut I) -> Result<String, String> {
+    match inline {
+        Some(value) => Ok(value.to_string()),
+        None => args.next().ok_or_else(|| format!("Missing value for {}", name)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Invalid value for {}: '{}'", name, value))
+}
+
+/// Parses a timeout in seconds, such as `2.5`.
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    match value.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(Duration::from_secs_f64(seconds)),
+        _ => Err(format!("Invalid timeout: '{}', expected a positive number of seconds", value)),
+    }
+}
+
+fn parse_part(value: &str) -> Result<u8, String> {
+    match value {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(format!("Invalid part: '{}', expected 1 or 2", value)),
+    }
+}
+
+fn parse_format(value: &str) -> Result<Format, String> {
+    match value {
+        "table" => Ok(Format::Table),
+        "json" => Ok(Format::Json),
+        "csv" => Ok(Format::Csv),
+        _ => Err(format!("Invalid format: '{}', expected table, json or csv", value)),
+    }
+}
+
+/// Parses the year of an event, the first of which was in 2015.
+fn parse_year(value: &str) -> Result<u16, String> {
+    match value.trim().parse::<u16>() {
+        Ok(year) if year >= 2015 => Ok(year),
+        _ => Err(format!("Invalid year: '{}', expected 2015 or later", value)),
+    }
+}
+
+fn parse_day(value: &str) -> Result<u8, String> {
+    value.trim().parse::<u8>().map_err(|_| format!("Invalid day: '{}'", value))
+}
+
+fn parse_days(value: &str) -> Result<Vec<u8>, String> {
+    let mut days: Vec<u8> = vec![];
+    for spec in value.split(',') {
+        let range = match spec.split_once("..") {
+            Some((from, to)) => {
+                let to = to.trim_start_matches('=');
+                RangeInclusive::new(parse_day(from)?, parse_day(to)?)
+            }
+            None => {
+                let day = parse_day(spec)?;
+                RangeInclusive::new(day, day)
+            }
+        };
+        if range.is_empty() {
+            return Err(format!("Empty day range: '{}'", spec));
+        }
+        days.extend(range);
+    }
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_no_arguments() {
+        assert_eq!(parse(args(&[])), Ok(Command::Run(Run { days: None, part: None, input: None, ..Run::default() })));
+        assert_eq!(parse(args(&["run"])), Ok(Command::Run(Run { days: None, part: None, input: None, ..Run::default() })));
+    }
+
+    #[test]
+    fn test_parse_day_and_part() {
+        assert_eq!(
+            parse(args(&["run", "4", "--part", "2"])),
+            Ok(Command::Run(Run { days: Some(vec![4]), part: Some(2), input: None, ..Run::default() }))
+        );
+        assert_eq!(
+            parse(args(&["4", "--part=1"])),
+            Ok(Command::Run(Run { days: Some(vec![4]), part: Some(1), input: None, ..Run::default() }))
+        );
+    }
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(
+            parse(args(&["run", "12", "--input", "-"])),
+            Ok(Command::Run(Run { days: Some(vec![12]), part: None, input: Some("-".to_string()), ..Run::default() }))
+        );
+        assert_eq!(
+            parse(args(&["--input=inputs/alice/day12.txt", "12"])),
+            Ok(Command::Run(Run { days: Some(vec![12]), part: None, input: Some("inputs/alice/day12.txt".to_string()), ..Run::default() }))
+        );
+        assert!(parse(args(&["run", "--input", "day12.txt"])).is_err());
+        assert!(parse(args(&["run", "1..2", "--input", "day12.txt"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_year_and_day() {
+        assert_eq!(
+            parse(args(&["--year", "2021", "--day", "5"])),
+            Ok(Command::Run(Run { year: Some(2021), days: Some(vec![5]), ..Run::default() }))
+        );
+        assert_eq!(
+            parse(args(&["run", "-y", "2020", "1..3"])),
+            Ok(Command::Run(Run { year: Some(2020), days: Some(vec![1, 2, 3]), ..Run::default() }))
+        );
+        assert_eq!(
+            parse(args(&["bench", "--year=2021", "-d", "12"])),
+            Ok(Command::Bench(Bench { run: Run { year: Some(2021), days: Some(vec![12]), ..Run::default() }, ..Bench::default() }))
+        );
+        assert!(parse(args(&["--day", "5", "6"])).is_err());
+        assert!(parse(args(&["--year", "2014"])).is_err());
+        assert!(parse(args(&["--year", "last"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_variant() {
+        assert_eq!(
+            parse(args(&["7", "--variant", "median"])),
+            Ok(Command::Run(Run { days: Some(vec![7]), variant: Some("median".to_string()), ..Run::default() }))
+        );
+        assert_eq!(
+            parse(args(&["bench", "12", "--variant=counting"])),
+            Ok(Command::Bench(Bench { run: Run { days: Some(vec![12]), variant: Some("counting".to_string()), ..Run::default() }, ..Bench::default() }))
+        );
+        assert!(parse(args(&["--variant"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_crosscheck() {
+        assert_eq!(parse(args(&["crosscheck"])), Ok(Command::Crosscheck(Run::default())));
+        assert_eq!(
+            parse(args(&["crosscheck", "7,12", "--part", "2"])),
+            Ok(Command::Crosscheck(Run { days: Some(vec![7, 12]), part: Some(2), ..Run::default() }))
+        );
+        assert!(parse(args(&["crosscheck", "--variant", "median"])).is_err());
+        assert!(parse(args(&["crosscheck", "--jobs", "2"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("3..7"), Ok(vec![3, 4, 5, 6, 7]));
+        assert_eq!(parse_days("3..=5"), Ok(vec![3, 4, 5]));
+        assert_eq!(parse_days("9,1..2,2"), Ok(vec![1, 2, 9]));
+        assert!(parse_days("7..3").is_err());
+        assert!(parse_days("x").is_err());
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            parse(args(&["run", "--format", "json"])),
+            Ok(Command::Run(Run { format: Format::Json, ..Run::default() }))
+        );
+        assert_eq!(
+            parse(args(&["1..3", "--format=csv"])),
+            Ok(Command::Run(Run { days: Some(vec![1, 2, 3]), format: Format::Csv, ..Run::default() }))
+        );
+        assert!(parse(args(&["run", "--format", "xml"])).is_err());
+        assert!(parse(args(&["bench", "--format", "json"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_jobs() {
+        assert_eq!(parse(args(&["run", "--jobs", "4"])), Ok(Command::Run(Run { jobs: 4, ..Run::default() })));
+        assert_eq!(parse(args(&["-j", "2"])), Ok(Command::Run(Run { jobs: 2, ..Run::default() })));
+        assert!(parse(args(&["run", "--jobs", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(
+            parse(args(&["run", "--timeout", "2.5"])),
+            Ok(Command::Run(Run { timeout: Some(Duration::from_millis(2500)), ..Run::default() }))
+        );
+        assert_eq!(
+            parse(args(&["crosscheck", "12", "--timeout=10"])),
+            Ok(Command::Crosscheck(Run { days: Some(vec![12]), timeout: Some(Duration::from_secs(10)), ..Run::default() }))
+        );
+        assert!(parse(args(&["run", "--timeout", "0"])).is_err());
+        assert!(parse(args(&["run", "--timeout", "soon"])).is_err());
+        assert!(parse(args(&["bench", "--timeout", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        assert_eq!(parse(args(&["run", "--verify"])), Ok(Command::Run(Run { verify: true, ..Run::default() })));
+        assert_eq!(
+            parse(args(&["13", "--verify", "--answers=mine.toml"])),
+            Ok(Command::Run(Run { days: Some(vec![13]), verify: true, answers: Some("mine.toml".to_string()), ..Run::default() }))
+        );
+        assert!(parse(args(&["bench", "--verify"])).is_err());
+        assert!(parse(args(&["13", "--input", "-", "--verify"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_explain() {
+        assert_eq!(
+            parse(args(&["4,13", "-v"])),
+            Ok(Command::Run(Run { days: Some(vec![4, 13]), explain: Some(vec![]), ..Run::default() }))
+        );
+        assert_eq!(
+            parse(args(&["--explain=13..14", "4,13"])),
+            Ok(Command::Run(Run { days: Some(vec![4, 13]), explain: Some(vec![13, 14]), ..Run::default() }))
+        );
+        assert_eq!(
+            parse(args(&["watch", "14", "--explain"])),
+            Ok(Command::Watch(Watch { run: Run { days: Some(vec![14]), explain: Some(vec![]), ..Run::default() }, examples: false }))
+        );
+        assert!(parse(args(&["--explain=four"])).is_err());
+        assert!(parse(args(&["bench", "-v"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        assert_eq!(
+            parse(args(&["watch", "14"])),
+            Ok(Command::Watch(Watch { run: Run { days: Some(vec![14]), ..Run::default() }, examples: false }))
+        );
+        assert_eq!(
+            parse(args(&["watch", "-e", "--day=7", "--variant", "median", "--input", "mine.txt", "--timeout", "3"])),
+            Ok(Command::Watch(Watch {
+                run: Run {
+                    days: Some(vec![7]),
+                    input: Some("mine.txt".to_string()),
+                    variant: Some("median".to_string()),
+                    timeout: Some(Duration::from_secs(3)),
+                    ..Run::default()
+                },
+                examples: true,
+            }))
+        );
+        assert!(parse(args(&["watch"])).is_err());
+        assert!(parse(args(&["watch", "1..3"])).is_err());
+        assert!(parse(args(&["watch", "7", "--input", "-"])).is_err());
+        assert!(parse(args(&["watch", "7", "--jobs", "2"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_repl() {
+        assert_eq!(parse(args(&["repl"])), Ok(Command::Repl(Repl { year: None })));
+        assert_eq!(parse(args(&["repl", "--year=2021"])), Ok(Command::Repl(Repl { year: Some(2021) })));
+        assert!(parse(args(&["repl", "13"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_serve() {
+        assert_eq!(parse(args(&["serve"])), Ok(Command::Serve(Serve::default())));
+        assert_eq!(
+            parse(args(&["serve", "--address", "0.0.0.0:8080", "--max-body=4096", "-j", "2", "--timeout", "5"])),
+            Ok(Command::Serve(Serve { address: "0.0.0.0:8080".to_string(), max_body: 4096, jobs: 2, timeout: Some(Duration::from_secs(5)) }))
+        );
+        assert!(parse(args(&["serve", "--jobs", "0"])).is_err());
+        assert!(parse(args(&["serve", "15"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_tui() {
+        assert_eq!(parse(args(&["tui"])), Ok(Command::Tui(Tui::default())));
+        assert_eq!(
+            parse(args(&["tui", "-y", "2021", "-j", "4", "--timeout=2", "--history", "mine.tsv"])),
+            Ok(Command::Tui(Tui { year: Some(2021), jobs: 4, timeout: Some(Duration::from_secs(2)), history: Some("mine.tsv".to_string()) }))
+        );
+        assert!(parse(args(&["tui", "7"])).is_err());
+        assert!(parse(args(&["tui", "--jobs", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_history() {
+        assert_eq!(
+            parse(args(&["13", "--history", "mine.tsv", "--no-history"])),
+            Ok(Command::Run(Run { days: Some(vec![13]), history: Some("mine.tsv".to_string()), no_history: true, ..Run::default() }))
+        );
+        assert_eq!(parse(args(&["history"])), Ok(Command::History(History::default())));
+        assert_eq!(
+            parse(args(&["history", "-y", "2021", "7..8", "--window=5", "--factor", "2", "--profile", "release", "-j", "4", "--history", "mine.tsv"])),
+            Ok(Command::History(History {
+                year: Some(2021),
+                days: Some(vec![7, 8]),
+                profile: Some("release".to_string()),
+                jobs: 4,
+                window: 5,
+                factor: 2.0,
+                path: Some("mine.tsv".to_string()),
+            }))
+        );
+        assert!(parse(args(&["history", "--window", "0"])).is_err());
+        assert!(parse(args(&["history", "--jobs", "0"])).is_err());
+        assert!(parse(args(&["history", "--factor", "-1"])).is_err());
+        assert!(parse(args(&["history", "--part", "1"])).is_err());
+        assert!(parse(args(&["bench", "--no-history"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_fetch() {
+        assert_eq!(parse(args(&["fetch", "7"])), Ok(Command::Fetch(Fetch { year: None, day: 7, base_url: None })));
+        assert_eq!(
+            parse(args(&["fetch", "--base-url", "http://localhost:8080", "25"])),
+            Ok(Command::Fetch(Fetch { year: None, day: 25, base_url: Some("http://localhost:8080".to_string()) }))
+        );
+        assert!(parse(args(&["fetch"])).is_err());
+        assert!(parse(args(&["fetch", "26"])).is_err());
+        assert!(parse(args(&["fetch", "1..3"])).is_err());
+        assert_eq!(parse(args(&["fetch", "--year", "2020", "1"])), Ok(Command::Fetch(Fetch { year: Some(2020), day: 1, base_url: None })));
+    }
+
+    #[test]
+    fn test_parse_submit() {
+        assert_eq!(parse(args(&["submit", "7", "2"])), Ok(Command::Submit(Submit { day: 7, part: 2, ..Submit::default() })));
+        assert_eq!(
+            parse(args(&["submit", "13", "2", "--answer", "HZLEHJRK", "--ledger=mine.tsv"])),
+            Ok(Command::Submit(Submit {
+                day: 13,
+                part: 2,
+                answer: Some("HZLEHJRK".to_string()),
+                ledger: Some("mine.tsv".to_string()),
+                ..Submit::default()
+            }))
+        );
+        assert!(parse(args(&["submit", "7"])).is_err());
+        assert!(parse(args(&["submit", "7", "3"])).is_err());
+        assert!(parse(args(&["submit", "7", "1", "2"])).is_err());
+        assert_eq!(
+            parse(args(&["submit", "-y", "2020", "7", "2"])),
+            Ok(Command::Submit(Submit { year: Some(2020), day: 7, part: 2, ..Submit::default() }))
+        );
+    }
+
+    #[test]
+    fn test_parse_new() {
+        assert_eq!(parse(args(&["new", "16"])), Ok(Command::New(New { year: None, day: 16, title: None })));
+        assert_eq!(
+            parse(args(&["new", "16", "--title", "Packet Decoder"])),
+            Ok(Command::New(New { year: None, day: 16, title: Some("Packet Decoder".to_string()) }))
+        );
+        assert!(parse(args(&["new"])).is_err());
+        assert!(parse(args(&["new", "0"])).is_err());
+        assert_eq!(
+            parse(args(&["new", "1", "--year=2022"])),
+            Ok(Command::New(New { year: Some(2022), day: 1, title: None }))
+        );
+    }
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(parse(args(&["--list"])), Ok(Command::List));
+        assert_eq!(parse(args(&["list"])), Ok(Command::List));
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert_eq!(parse(args(&["bench"])), Ok(Command::Bench(Bench::default())));
+        assert_eq!(
+            parse(args(&["bench", "12", "--part", "2", "--warmup=1", "--iterations", "5", "--baseline", "base.tsv", "--threshold", "25"])),
+            Ok(Command::Bench(Bench {
+                run: Run { days: Some(vec![12]), part: Some(2), input: None, ..Run::default() },
+                warmup: 1,
+                iterations: 5,
+                baseline: Some("base.tsv".to_string()),
+                threshold: 25.0,
+                ..Bench::default()
+            }))
+        );
+        assert!(parse(args(&["bench", "--iterations", "0"])).is_err());
+        assert!(parse(args(&["bench", "--threshold", "ten"])).is_err());
+        assert!(parse(args(&["bench", "--threshold", "-5"])).is_err());
+        assert!(parse(args(&["bench", "--threshold=NaN"])).is_err());
+        assert_eq!(
+            parse(args(&["bench", "--timeout", "2"])),
+            Ok(Command::Bench(Bench { run: Run { timeout: Some(Duration::from_secs(2)), ..Run::default() }, ..Bench::default() }))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse(args(&["run", "4", "--part", "3"])).is_err());
+        assert!(parse(args(&["run", "4", "--part"])).is_err());
+        assert!(parse(args(&["run", "4", "5"])).is_err());
+        assert!(parse(args(&["--verbose"])).is_err());
+        assert!(parse(args(&["run", "--warmup", "3"])).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Environment variable holding the adventofcode.com session cookie.
+pub(crate) const SESSION_VAR: &str = "AOC_SESSION";
+/// Environment variable overriding the server, e.g. to test against a local mock.
+pub(crate) const BASE_URL_VAR: &str = "AOC_BASE_URL";
+pub(crate) const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+const USER_AGENT: &str = "github.com/larsmoil/adventofcode2021 by larsmoil";
+
+/// A client for the Advent of Code website, authenticated by a session cookie.
+pub(crate) struct Client {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub(crate) fn new(base_url: &str, session: &str) -> Client {
+        let agent = ureq::AgentBuilder::new().user_agent(USER_AGENT).timeout(Duration::from_secs(30)).build();
+        Client { base_url: base_url.trim_end_matches('/').to_string(), session: session.to_string(), agent }
+    }
+
+    /// Creates a client for `base_url`, or `$AOC_BASE_URL`, with the session from [`session`].
+    pub(crate) fn from_env(base_url: Option<String>) -> Result<Client, String> {
+        let base_url = base_url
+            .or_else(|| std::env::var(BASE_URL_VAR).ok())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+        Ok(Client::new(&base_url, &session(std::env::var(SESSION_VAR).ok(), config_path().as_deref())?))
+    }
+
+    pub(crate) fn input(&self, year: u16, day: u8) -> Result<String, String> {
+        let url = format!("{}/{}/day/{}/input", self.base_url, year, day);
+        let response = self.agent.get(&url).set("Cookie", &format!("session={}", self.session)).call();
+        match response {
+            Ok(response) => response.into_string().map_err(|e| format!("Failed to read {}: {}", url, e)),
+            Err(ureq::Error::Status(404, _)) => Err(format!("No input at {}: the puzzle is not unlocked yet", url)),
+            Err(ureq::Error::Status(400, _)) | Err(ureq::Error::Status(500, _)) => {
+                Err(format!("Request to {} was rejected: the session cookie is invalid or expired", url))
+            }
+            Err(e) => Err(format!("Request to {} failed: {}", url, e)),
+        }
+    }
+
+    /// Submits `answer` to `part` of a puzzle, returning the response page.
+    pub(crate) fn answer(&self, year: u16, day: u8, part: u8, answer: &str) -> Result<String, String> {
+        let url = format!("{}/{}/day/{}/answer", self.base_url, year, day);
+        let response = self
+            .agent
+            .post(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .send_form(&[("level", &part.to_string()), ("answer", answer)]);
+        match response {
+            Ok(response) => response.into_string().map_err(|e| format!("Failed to read {}: {}", url, e)),
+            Err(ureq::Error::Status(400, _)) | Err(ureq::Error::Status(500, _)) => {
+                Err(format!("Request to {} was rejected: the session cookie is invalid or expired", url))
+            }
+            Err(e) => Err(format!("Request to {} failed: {}", url, e)),
+        }
+    }
+}
+
+/// The session cookie file, `$XDG_CONFIG_HOME/adventofcode/session` or `~/.config/adventofcode/session`.
+pub(crate) fn config_path() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("adventofcode").join("session"))
+}
+
+/// Returns the session cookie from `env` if set, or else the contents of the `config` file.
+fn session(env: Option<String>, config: Option<&Path>) -> Result<String, String> {
+    if let Some(session) = env.map(|session| session.trim().to_string()).filter(|session| !session.is_empty()) {
+        return Ok(session);
+    }
+    let missing = || {
+        format!(
+            "No session cookie: set ${} or write it to {}",
+            SESSION_VAR,
+            config.map(|path| format!("'{}'", path.display())).unwrap_or_else(|| "a config file".to_string())
+        )
+    };
+    let session = std::fs::read_to_string(config.ok_or_else(missing)?).map_err(|_| missing())?;
+    Some(session.trim().to_string()).filter(|session| !session.is_empty()).ok_or_else(missing)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum Fetched {
+    /// The input was already cached, and was not requested again.
+    Cached,
+    Downloaded,
+}
+
+/// Downloads the input of `day` to `path`, unless it is already there.
+pub(crate) fn fetch(client: impl FnOnce() -> Result<Client, String>, year: u16, day: u8, path: &Path) -> Result<Fetched, String> {
+    if path.exists() {
+        return Ok(Fetched::Cached);
+    }
+    let inp = client()?.input(year, day)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    // Write to a temporary file first, so an interrupted fetch never leaves a partial input behind.
+    let partial = path.with_extension("txt.partial");
+    std::fs::write(&partial, inp)
+        .and_then(|_| std::fs::rename(&partial, path))
+        .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    Ok(Fetched::Downloaded)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    use super::*;
+
+    /// Serves `responses` (status line and body) to one connection each, returning the received requests.
+    pub(crate) fn mock_server(responses: Vec<(&'static str, &'static str)>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = vec![];
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    request.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut content = vec![0; length];
+                std::io::Read::read_exact(&mut reader, &mut content).unwrap();
+                request.push_str(&String::from_utf8(content).unwrap());
+                requests.push(request);
+                write!(stream, "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body).unwrap();
+            }
+            requests
+        });
+        (base_url, handle)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("adventofcode-2021-client-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_fetch_caches() {
+        let (base_url, server) = mock_server(vec![("200 OK", "3,4,3,1,2\n")]);
+        let path = temp_dir("fetch").join("day06.txt");
+
+        let client = || Ok(Client::new(&base_url, "cookie"));
+        assert_eq!(fetch(client, 2021, 6, &path), Ok(Fetched::Downloaded));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "3,4,3,1,2\n");
+        // The mock only answers once, so a second request would fail.
+        assert_eq!(fetch(client, 2021, 6, &path), Ok(Fetched::Cached));
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("GET /2021/day/6/input HTTP/1.1\r\n"), "{}", requests[0]);
+        assert!(requests[0].contains("Cookie: session=cookie\r\n"), "{}", requests[0]);
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let (base_url, server) = mock_server(vec![("404 Not Found", "Please don't repeatedly request this endpoint"), ("400 Bad Request", "")]);
+        let path = temp_dir("errors").join("day25.txt");
+        let client = || Ok(Client::new(&base_url, "cookie"));
+
+        let error = fetch(client, 2021, 25, &path).unwrap_err();
+        assert!(error.contains("not unlocked yet"), "{}", error);
+        let error = fetch(client, 2021, 25, &path).unwrap_err();
+        assert!(error.contains("session cookie is invalid"), "{}", error);
+        assert!(!path.exists());
+        server.join().unwrap();
+
+        assert_eq!(fetch(|| Err("No session cookie".to_string()), 2021, 25, &path), Err("No session cookie".to_string()));
+    }
+
+    #[test]
+    fn test_session() {
+        let dir = temp_dir("session");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session");
+        assert_eq!(session(Some("from-env\n".to_string()), Some(&path)), Ok("from-env".to_string()));
+        assert!(session(None, Some(&path)).unwrap_err().starts_with("No session cookie"));
+        std::fs::write(&path, "from-file\n").unwrap();
+        assert_eq!(session(Some("".to_string()), Some(&path)), Ok("from-file".to_string()));
+        assert!(session(None, None).is_err());
+    }
+}
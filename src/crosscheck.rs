@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use crate::input::Resolver;
+use crate::problem::Answer;
+use crate::registry;
+use crate::report;
+use crate::runner::{self, Failure, StepError};
+
+/// Returns the days of `year` with more than one variant, which crosscheck runs when no days are given.
+pub(crate) fn days(year: u16) -> Vec<u8> {
+    registry::days(year)
+        .into_iter()
+        .filter(|day| registry::find(year, *day).map(|puzzle| !puzzle.variants.is_empty()).unwrap_or(false))
+        .collect()
+}
+
+/// Whether `answer` agrees with the answer of the first variant, `expected`. Failures never agree.
+fn agrees(answer: &Result<Answer, StepError>, expected: &Result<Answer, StepError>) -> bool {
+    matches!((answer, expected), (Ok(answer), Ok(expected)) if answer == expected)
+}
+
+/// Runs `parts` of every variant of `days` on the same input, printing the answers of the variants side by side.
+/// Returns false if any variant failed or disagreed with the default variant.
+pub(crate) fn run(year: u16, days: &[u8], resolver: &Resolver, parts: &[u8], timeout: Option<Duration>) -> bool {
+    let (mut agreed, mut disagreed) = (0, 0);
+    for &day in days {
+        let puzzle = registry::find(year, day).expect("registered day");
+        // Read the input once, as it may come from stdin.
+        let inp = match resolver.resolve(year, day) {
+            Ok(inp) => inp,
+            Err(message) => {
+                eprintln!("{}", message);
+                disagreed += parts.len();
+                continue;
+            }
+        };
+        let names = puzzle.variant_names();
+        let results: Vec<_> = names
+            .iter()
+            .map(|name| runner::solve(year, day, puzzle.variant(name).expect("registered variant"), &inp, parts, timeout))
+            .collect();
+
+        let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+        for (name, result) in names.iter().zip(&results) {
+            match &result.failure {
+                Some(Failure::Parse(e)) => println!("day{:02} - parse: {:<width$} {:>15} ({:.2?}) {}", day, name, e.label(), result.parse, e),
+                _ => println!("day{:02} - parse: {:<width$} {:>15} ({:.2?})", day, name, "", result.parse),
+            }
+        }
+        for (i, &part) in parts.iter().enumerate() {
+            let answers: Vec<(Duration, Result<Answer, StepError>)> = results
+                .iter()
+                .map(|result| match (&result.failure, result.parts.get(i)) {
+                    (None, Some(part)) => (part.elapsed, part.answer.clone()),
+                    (Some(Failure::Parse(e)), _) => (Duration::ZERO, Err(e.clone())),
+                    _ => unreachable!("every part was run on the resolved input"),
+                })
+                .collect();
+            let expected = &answers[0].1;
+            for (name, (elapsed, answer)) in names.iter().zip(&answers) {
+                let verdict = if agrees(answer, expected) { String::new() } else { " MISMATCH".to_string() };
+                match answer {
+                    Ok(Answer::Image(rows)) => {
+                        println!("day{:02} - pt{}: {:<width$} {:>15} ({:.2?}){}", day, part, name, report::image_size(rows), elapsed, verdict)
+                    }
+                    Ok(answer) => println!("day{:02} - pt{}: {:<width$} {:>15} ({:.2?}){}", day, part, name, answer, elapsed, verdict),
+                    Err(e) => println!("day{:02} - pt{}: {:<width$} {:>15} ({:.2?}) {}", day, part, name, e.label(), elapsed, e),
+                }
+            }
+            if answers.iter().all(|(_, answer)| agrees(answer, expected)) {
+                agreed += 1;
+            } else {
+                disagreed += 1;
+            }
+        }
+    }
+    println!("crosscheck: {} agreed, {} disagreed or failed", agreed, disagreed);
+    disagreed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::SolveError;
+
+    #[test]
+    fn test_days() {
+        assert_eq!(days(2021), vec![7, 12]);
+        assert!(days(2020).is_empty());
+    }
+
+    #[test]
+    fn test_agrees() {
+        let unsolvable = Err(StepError::Solve(SolveError::unsolvable(7, "no crabs")));
+        assert!(agrees(&Ok(Answer::Integer(37)), &Ok(Answer::Integer(37))));
+        assert!(!agrees(&Ok(Answer::Integer(36)), &Ok(Answer::Integer(37))));
+        assert!(!agrees(&unsolvable, &unsolvable));
+        assert!(!agrees(&Ok(Answer::Integer(37)), &unsolvable));
+    }
+
+    #[test]
+    fn test_run() {
+        let path = std::env::temp_dir().join(format!("adventofcode-2021-crosscheck-{}.txt", std::process::id()));
+        std::fs::write(&path, "16,1,2,0,4,2,7,1,2,14").unwrap();
+        let resolver = Resolver { path: Some(path.display().to_string()), dir: std::env::temp_dir(), embedded: false };
+        assert!(run(2021, &[7], &resolver, &[1, 2], None));
+        // Both variants fail to parse the input of another day.
+        assert!(!run(2021, &[12], &resolver, &[1], None));
+    }
+}
@@ -1,22 +1,36 @@
-use crate::problem::Solver;
+//! [Day 1: Sonar Sweep](https://adventofcode.com/2021/day/1).
 
+use crate::problem::{Answer, parse, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 1;
+
+/// The solver of day 1.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Sonar Sweep", solver: Day {}, input: "input01.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let measurements = measurements(inp);
+    type Parsed = Vec<i32>;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        measurements(inp)
+    }
+    fn pt1(&self, measurements: &Self::Parsed) -> Result<Answer, SolveError> {
         let mut increased = 0;
         let mut last = measurements[0];
-        for measurement in measurements {
+        for &measurement in measurements {
             if last < measurement {
                 increased += 1
             }
             last = measurement
         }
-        increased.to_string()
+        Ok(increased.into())
     }
-    fn pt2(&self, inp: &str) -> String {
-        let measurements = measurements(inp);
+    fn pt2(&self, measurements: &Self::Parsed) -> Result<Answer, SolveError> {
+        if measurements.len() < 3 {
+            return Err(SolveError::unsolvable(DAY, "at least three measurements are required"));
+        }
         let windows: Vec<(i32, i32, i32)> = measurements
             .iter()
             .enumerate()
@@ -36,21 +50,17 @@ impl Solver for Day {
             }
             last = sum
         }
-        increased.to_string()
+        Ok(increased.into())
     }
 }
 
-fn measurements(inp: &str) -> Vec<i32> {
+fn measurements(inp: &str) -> Result<Vec<i32>, SolveError> {
     inp
         .split('\n')
-        .map(|a| a.parse::<i32>().unwrap())
+        .map(|a| parse::<i32>(DAY, inp, a))
         .collect()
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input01.txt").trim()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,21 +91,29 @@ mod tests {
 
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input_1()), "7")
+        assert_eq!(Day {}.solve_pt1(example_input_1()), Ok(Answer::Integer(7)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "1715")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(1715)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input_2()), "5")
+        assert_eq!(Day {}.solve_pt2(example_input_2()), Ok(Answer::Integer(5)))
+    }
+
+    #[test]
+    fn test_pt1_malformed() {
+        assert_eq!(
+            Day {}.solve_pt1("199\n200\n2O8").unwrap_err().to_string(),
+            "day 01, line 3, column 1: invalid digit found in string ('2O8')"
+        )
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "1739")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(1739)))
     }
 }
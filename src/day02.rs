@@ -1,46 +1,57 @@
-use crate::problem::Solver;
+//! [Day 2: Dive!](https://adventofcode.com/2021/day/2).
 
+use crate::problem::{Answer, parse, split_once, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 2;
+
+/// The solver of day 2.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Dive!", solver: Day {}, input: "input02.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let coordinate = coordinate(inp);
-        (coordinate.0 * coordinate.1).to_string()
+    type Parsed = Vec<(i32, i32)>;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        course_adjustments(inp)
+    }
+    fn pt1(&self, course_adjustments: &Self::Parsed) -> Result<Answer, SolveError> {
+        let coordinate = coordinate(course_adjustments);
+        Ok((coordinate.0 * coordinate.1).into())
     }
-    fn pt2(&self, inp: &str) -> String {
-        let coordinate = coordinate_with_aim(inp);
-        (coordinate.0 * coordinate.1).to_string()
+    fn pt2(&self, course_adjustments: &Self::Parsed) -> Result<Answer, SolveError> {
+        let coordinate = coordinate_with_aim(course_adjustments);
+        Ok((coordinate.0 * coordinate.1).into())
     }
 }
 
-fn course_adjustments(inp: &str) -> Vec<(i32, i32)> {
+fn course_adjustments(inp: &str) -> Result<Vec<(i32, i32)>, SolveError> {
     inp
         .split('\n')
         .map(|a| {
-            let split = a.split(' ').collect::<Vec<&str>>();
-            let direction = split.first().unwrap();
-            let amount = split.last().unwrap();
-            let amount = amount.parse::<i32>().unwrap();
-            match *direction {
-                "forward" => (amount, 0),
-                "up" => (0, -amount),
-                "down" => (0, amount),
-                _ => panic!("Unknown direction: '{}'!", direction)
+            let (direction, amount) = split_once(DAY, inp, a, " ")?;
+            let amount = parse::<i32>(DAY, inp, amount)?;
+            match direction {
+                "forward" => Ok((amount, 0)),
+                "up" => Ok((0, -amount)),
+                "down" => Ok((0, amount)),
+                _ => Err(SolveError::parse(DAY, inp, direction, "unknown direction"))
             }
         })
         .collect()
 }
 
-fn coordinate(inp: &str) -> (i32, i32) {
-    course_adjustments(inp)
+fn coordinate(course_adjustments: &[(i32, i32)]) -> (i32, i32) {
+    course_adjustments
         .iter()
         .fold((0, 0), |a, b| {
             (a.0 + b.0, a.1 + b.1)
         })
 }
 
-fn coordinate_with_aim(inp: &str) -> (i32, i32) {
-    let coordinate = course_adjustments(inp)
+fn coordinate_with_aim(course_adjustments: &[(i32, i32)]) -> (i32, i32) {
+    let coordinate = course_adjustments
         .iter()
         .fold((0, 0, 0), |a, b| {
             let aim = a.2 + b.1;
@@ -49,10 +60,6 @@ fn coordinate_with_aim(inp: &str) -> (i32, i32) {
     (coordinate.0, coordinate.1)
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input02.txt").trim()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,7 +75,7 @@ forward 2"
 
     #[test]
     fn test_course_adjustments_example() {
-        assert_eq!(course_adjustments(example_input()), [
+        assert_eq!(course_adjustments(example_input()).unwrap(), [
             (5, 0),
             (0, 5),
             (8, 0),
@@ -78,33 +85,45 @@ forward 2"
         ])
     }
 
+    #[test]
+    fn test_course_adjustments_malformed() {
+        assert_eq!(
+            course_adjustments("forward 5\nbackward 5").unwrap_err().to_string(),
+            "day 02, line 2, column 1: unknown direction ('backward')"
+        );
+        assert_eq!(
+            course_adjustments("forward 5\ndown five").unwrap_err().to_string(),
+            "day 02, line 2, column 6: invalid digit found in string ('five')"
+        );
+    }
+
     #[test]
     fn test_coordinate_example() {
-        assert_eq!(coordinate(example_input()), (15, 10))
+        assert_eq!(coordinate(&course_adjustments(example_input()).unwrap()), (15, 10))
     }
 
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "150")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(150)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "1580000")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(1580000)))
     }
 
     #[test]
     fn test_coordinate_with_aim_example() {
-        assert_eq!(coordinate_with_aim(example_input()), (15, 60))
+        assert_eq!(coordinate_with_aim(&course_adjustments(example_input()).unwrap()), (15, 60))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "900")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(900)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "1251263225")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(1251263225)))
     }
 }
@@ -1,32 +1,54 @@
-use crate::problem::Solver;
+//! [Day 3: Binary Diagnostic](https://adventofcode.com/2021/day/3).
 
+use crate::problem::{Answer, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 3;
+
+/// The solver of day 3.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Binary Diagnostic", solver: Day {}, input: "input03.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let vals = epsilon_gamma(inp);
-        (vals.0 * vals.1).to_string()
+    /// The diagnostic values and their number of bits.
+    type Parsed = (Vec<u32>, usize);
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        report(inp)
     }
-    fn pt2(&self, inp: &str) -> String {
-        let vals = epsilon_gamma(inp);
-        (vals.2 * vals.3).to_string()
+    fn pt1(&self, (values, num_columns): &Self::Parsed) -> Result<Answer, SolveError> {
+        let vals = epsilon_gamma(values, *num_columns);
+        Ok((vals.0 * vals.1).into())
+    }
+    fn pt2(&self, (values, num_columns): &Self::Parsed) -> Result<Answer, SolveError> {
+        let vals = epsilon_gamma(values, *num_columns);
+        Ok((vals.2 * vals.3).into())
     }
 }
 
-fn epsilon_gamma(inp: &str) -> (u32, u32, u32, u32) {
+fn report(inp: &str) -> Result<(Vec<u32>, usize), SolveError> {
     let num_columns = inp.split('\n').map(|s| s.trim()).collect::<Vec<&str>>().first().unwrap().len();
     let lines = inp.split('\n').map(|s| s.trim());
     let values: Vec<u32> = lines
-        .map(|l| u32::from_str_radix(l, 2).unwrap())
-        .collect();
+        .map(|l| {
+            if l.len() != num_columns {
+                return Err(SolveError::parse(DAY, inp, l, format!("expected {} bits", num_columns)));
+            }
+            u32::from_str_radix(l, 2).map_err(|e| SolveError::parse(DAY, inp, l, e.to_string()))
+        })
+        .collect::<Result<Vec<u32>, SolveError>>()?;
+    Ok((values, num_columns))
+}
 
+fn epsilon_gamma(values: &[u32], num_columns: usize) -> (u32, u32, u32, u32) {
     let half_or_more_ones = |ones, size| if 2 * ones >= size as u32 { 1 } else { 0 };
     let half_or_more_zeros = |ones, size| if 2 * ones < size as u32 { 1 } else { 0 };
 
-    let gamma = mask(&values, num_columns, half_or_more_ones);
-    let epsilon = mask(&values, num_columns, half_or_more_zeros);
-    let oxygen_generator_rating = rating(&values, num_columns, half_or_more_ones);
-    let scrubber_rating = rating(&values, num_columns, half_or_more_zeros);
+    let gamma = mask(values, num_columns, half_or_more_ones);
+    let epsilon = mask(values, num_columns, half_or_more_zeros);
+    let oxygen_generator_rating = rating(values, num_columns, half_or_more_ones);
+    let scrubber_rating = rating(values, num_columns, half_or_more_zeros);
 
     (epsilon, gamma, oxygen_generator_rating, scrubber_rating)
 }
@@ -54,10 +76,6 @@ fn rating(values: &[u32], column: usize, cmp: fn(u32, usize) -> u32) -> u32 {
     }
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input03.txt").trim()
-}
-
 fn mask(values: &[u32], columns: usize, cmp: fn(num_ones: u32, num_values: usize) -> u32) -> u32 {
     if columns < 1 {
         0
@@ -109,26 +127,39 @@ mod tests {
 
     #[test]
     fn test_epsilon_gamma() {
-        assert_eq!(epsilon_gamma(example_input()), (9, 22, 23, 10))
+        let (values, num_columns) = report(example_input()).unwrap();
+        assert_eq!(epsilon_gamma(&values, num_columns), (9, 22, 23, 10))
+    }
+
+    #[test]
+    fn test_report_malformed() {
+        assert_eq!(
+            report("00100\n11120").unwrap_err().to_string(),
+            "day 03, line 2, column 1: invalid digit found in string ('11120')"
+        );
+        assert_eq!(
+            report("00100\n1110").unwrap_err().to_string(),
+            "day 03, line 2, column 1: expected 5 bits ('1110')"
+        );
     }
 
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "198")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(198)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "2743844")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(2743844)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "230")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(230)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "6677951")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(6677951)))
     }
 }
@@ -1,23 +1,34 @@
-use crate::problem::Solver;
+//! [Day 4: Giant Squid](https://adventofcode.com/2021/day/4).
 
+use crate::problem::{Answer, parse, split_once, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 4;
+
+/// The solver of day 4.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Giant Squid", solver: Day {}, input: "input04.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let mut game = Game::new(inp);
-        game.solve().first().unwrap().to_string()
+    type Parsed = Game;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        Game::new(inp)
     }
-    fn pt2(&self, inp: &str) -> String {
-        let mut game = Game::new(inp);
-        game.solve().last().unwrap().to_string()
+    fn pt1(&self, game: &Self::Parsed) -> Result<Answer, SolveError> {
+        let mut game = game.clone();
+        let score = game.solve().first().copied().ok_or_else(|| SolveError::unsolvable(DAY, "no board wins"))?;
+        Ok(score.into())
+    }
+    fn pt2(&self, game: &Self::Parsed) -> Result<Answer, SolveError> {
+        let mut game = game.clone();
+        let score = game.solve().last().copied().ok_or_else(|| SolveError::unsolvable(DAY, "not every board wins"))?;
+        Ok(score.into())
     }
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input04.txt").trim()
-}
-
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 struct Board {
     numbers: Vec<(u32, bool)>,
     solved: bool,
@@ -31,7 +42,7 @@ impl Board {
             self.numbers[index].1 = true;
         }
 
-        self.solved = self.solved || vec![self.cols(), self.rows()]
+        self.solved = self.solved || [self.cols(), self.rows()]
             .iter()
             .flat_map(|a| a.iter())
             .map(|v| v.to_owned())
@@ -71,8 +82,9 @@ impl Board {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Game {
+/// The bingo game: the numbers to draw and the boards to mark them on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Game {
     numbers: Vec<u32>,
     boards: Vec<Board>,
 }
@@ -80,11 +92,12 @@ struct Game {
 impl Game {
     fn solve(&mut self) -> Vec<u32> {
         let mut scores: Vec<u32> = vec![];
-        for number in &self.numbers {
+        for (draw, number) in self.numbers.iter().enumerate() {
             for i in 0..self.boards.len() {
                 let board = self.boards.get_mut(i).unwrap();
                 if !board.solved {
                     if let Some(score) = board.draw(*number) {
+                        tracing::debug!(board = i + 1, draw = draw + 1, number, score = number * score, "board won");
                         scores.push(number * score);
                         let finished = scores.len() == self.boards.len();
                         if finished {
@@ -97,35 +110,38 @@ impl Game {
         vec![]
     }
 
-    fn new(inp: &str) -> Game {
-        let (numbers, boards) = inp.split_once("\n\n").unwrap();
+    fn new(inp: &str) -> Result<Game, SolveError> {
+        let (numbers, boards) = split_once(DAY, inp, inp, "\n\n")?;
         let numbers = numbers
             .split(',')
-            .map(|v| v.parse::<u32>().unwrap())
-            .collect();
+            .map(|v| parse::<u32>(DAY, inp, v))
+            .collect::<Result<Vec<u32>, SolveError>>()?;
         let board_numbers: Vec<Vec<(u32, bool)>> = boards
             .split("\n\n")
             .map(|board| {
                 let board_numbers: Vec<(u32, bool)> = board
                     .split('\n')
-                    .map(|row|
+                    .flat_map(|row|
                         row
                             .split_whitespace()
-                            .map(|num| (num.parse::<u32>().unwrap(), false))
+                            .map(|num| parse::<u32>(DAY, inp, num).map(|num| (num, false)))
                     )
-                    .flatten()
-                    .collect();
-                board_numbers
+                    .collect::<Result<Vec<(u32, bool)>, SolveError>>()?;
+                let rows = board.split('\n').count();
+                if board_numbers.len() != rows * rows {
+                    return Err(SolveError::parse(DAY, inp, board, format!("expected a {}x{} board", rows, rows)));
+                }
+                Ok(board_numbers)
             })
-            .collect();
+            .collect::<Result<Vec<Vec<(u32, bool)>>, SolveError>>()?;
         let mut boards: Vec<Board> = Vec::new();
         for board in board_numbers {
             boards.push(Board { numbers: board, solved: false });
         }
-        Game {
+        Ok(Game {
             numbers,
             boards,
-        }
+        })
     }
 }
 
@@ -234,14 +250,14 @@ mod tests {
             ],
             solved: false,
         };
-        assert_eq!(false, board.solved);
+        assert!(!board.solved);
         board.draw(8);
-        assert_eq!(true, board.solved);
+        assert!(board.solved);
     }
 
     #[test]
     fn test_game_new() {
-        let actual = Game::new(example_input());
+        let actual = Game::new(example_input()).unwrap();
         let expected = Game {
             numbers: vec![7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19, 3, 26, 1],
             boards: vec![
@@ -255,29 +271,49 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_game_new_malformed() {
+        assert_eq!(
+            Game::new("7,4,x\n\n1 2\n3 4").unwrap_err().to_string(),
+            "day 04, line 1, column 5: invalid digit found in string ('x')"
+        );
+        assert_eq!(
+            Game::new("7,4\n\n1 2\n3 4\n\n1 2\n3 -4").unwrap_err().to_string(),
+            "day 04, line 7, column 3: invalid digit found in string ('-4')"
+        );
+        assert_eq!(
+            Game::new("7,4\n\n1 2\n3").unwrap_err().to_string(),
+            "day 04, line 3, column 1: expected a 2x2 board ('1 2\\n3')"
+        );
+        assert_eq!(
+            Game::new("7,4").unwrap_err().to_string(),
+            "day 04, line 1, column 1: expected '\\n\\n' ('7,4')"
+        );
+    }
+
     #[test]
     fn test_game_solve() {
-        let actual = Game::new(example_input()).solve();
+        let actual = Game::new(example_input()).unwrap().solve();
         assert_eq!(actual, vec![4512, 2192, 1924]);
     }
 
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "4512")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(4512)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "65325")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(65325)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "1924")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(1924)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "4624")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(4624)))
     }
 }
@@ -1,24 +1,37 @@
+//! [Day 5: Hydrothermal Venture](https://adventofcode.com/2021/day/5).
+
 use std::cmp::{max, min};
 use std::ops::RangeInclusive;
-use crate::problem::Solver;
+use crate::problem::{Answer, parse, split_once, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 5;
 
+/// The solver of day 5.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Hydrothermal Venture", solver: Day {}, input: "input05.txt");
+
 type Point = (i64, i64);
 type Line = (Point, Point);
 
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let lines: Vec<Line> = lines(inp).iter().filter(|line| hor_vert_line(line)).copied().collect();
-        solve(&lines).to_string()
+    type Parsed = Vec<Line>;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        lines(inp)
+    }
+    fn pt1(&self, lines: &Self::Parsed) -> Result<Answer, SolveError> {
+        let lines: Vec<Line> = lines.iter().filter(|line| hor_vert_line(line)).copied().collect();
+        Ok(solve(&lines).into())
     }
-    fn pt2(&self, inp: &str) -> String {
-        let lines: Vec<Line> = lines(inp)
+    fn pt2(&self, lines: &Self::Parsed) -> Result<Answer, SolveError> {
+        let lines: Vec<Line> = lines
             .iter()
             .filter(|line| hor_vert_line(line) || diagonal_line(line))
             .copied()
             .collect();
-        solve(&lines).to_string()
+        Ok(solve(&lines).into())
     }
 }
 
@@ -48,27 +61,29 @@ fn solve(lines: &[Line]) -> i64 {
     at_least_twice as i64
 }
 
-fn lines(inp: &str) -> Vec<Line> {
+fn lines(inp: &str) -> Result<Vec<Line>, SolveError> {
+    let point = |coordinate: &str| -> Result<Point, SolveError> {
+        let (x, y) = split_once(DAY, inp, coordinate, ",")?;
+        let x = parse::<i64>(DAY, inp, x)?;
+        let y = parse::<i64>(DAY, inp, y)?;
+        if x < 0 || y < 0 {
+            return Err(SolveError::parse(DAY, inp, coordinate, "coordinates must not be negative"));
+        }
+        Ok((x, y))
+    };
     inp
         .split('\n')
         .map(|line| {
-            let coordinates: Vec<Point> = line
-                .split(" -> ")
-                .map(|coordinate| {
-                    let points: Vec<i64> = coordinate.split(',').map(|point| point.parse::<i64>().unwrap()).collect();
-                    let (x, y) = (points[0], points[1]);
-                    (x, y)
-                })
-                .collect();
-            let line: Line = (coordinates[0], coordinates[1]);
-            line
+            let (from, to) = split_once(DAY, inp, line, " -> ")?;
+            let line: Line = (point(from)?, point(to)?);
+            Ok(line)
         })
         .collect()
 }
 
 fn diagonal_line(line: &Line) -> bool {
-    let delta_x = (line.0.0 - line.1.0 as i64).abs();
-    let delta_y = (line.0.1 - line.1.1 as i64).abs();
+    let delta_x = (line.0.0 - line.1.0).abs();
+    let delta_y = (line.0.1 - line.1.1).abs();
 
     delta_x == delta_y
 }
@@ -93,10 +108,6 @@ fn points(map: (Point, Point)) -> Vec<i64> {
     [0].repeat((xs * ys) as usize)
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input05.txt").trim()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,7 +140,23 @@ mod tests {
                 ((0, 0), (8, 8)),
                 ((5, 5), (8, 2)),
             ],
-            lines(example_input())
+            lines(example_input()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_lines_malformed() {
+        assert_eq!(
+            lines("0,9 -> 5,9\n8,0 => 0,8").unwrap_err().to_string(),
+            "day 05, line 2, column 1: expected ' -> ' ('8,0 => 0,8')"
+        );
+        assert_eq!(
+            lines("0,9 -> 5,9\n8,0 -> 0;8").unwrap_err().to_string(),
+            "day 05, line 2, column 8: expected ',' ('0;8')"
+        );
+        assert_eq!(
+            lines("0,9 -> 5,-9").unwrap_err().to_string(),
+            "day 05, line 1, column 8: coordinates must not be negative ('5,-9')"
         );
     }
 
@@ -142,7 +169,7 @@ mod tests {
                 ((0, 0), (8, 8)),
                 ((5, 5), (8, 2)),
             ],
-            lines(example_input()).iter().filter(|line| diagonal_line(line)).copied().collect::<Vec<Line>>()
+            lines(example_input()).unwrap().iter().filter(|line| diagonal_line(line)).copied().collect::<Vec<Line>>()
         )
     }
 
@@ -157,7 +184,7 @@ mod tests {
                 ((0, 9), (2, 9)),
                 ((3, 4), (1, 4)),
             ],
-            lines(example_input()).iter().filter(|line| hor_vert_line(line)).copied().collect::<Vec<Line>>()
+            lines(example_input()).unwrap().iter().filter(|line| hor_vert_line(line)).copied().collect::<Vec<Line>>()
         );
     }
 
@@ -165,7 +192,7 @@ mod tests {
     fn test_map() {
         assert_eq!(
             ((0, 9), (0, 9)),
-            map(&lines(example_input()))
+            map(&lines(example_input()).unwrap())
         );
     }
 
@@ -173,27 +200,27 @@ mod tests {
     fn test_points() {
         assert_eq!(
             [0].repeat(100),
-            points(map(&lines(example_input())))
+            points(map(&lines(example_input()).unwrap()))
         );
     }
 
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "5")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(5)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "5576")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(5576)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "12")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(12)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "18144")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(18144)))
     }
 }
@@ -1,20 +1,32 @@
-use crate::problem::Solver;
+//! [Day 6: Lanternfish](https://adventofcode.com/2021/day/6).
 
+use crate::problem::{Answer, parse, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 6;
+
+/// The solver of day 6.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Lanternfish", solver: Day {}, input: "input06.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let initial_fish = fish(inp);
-        solve(80, initial_fish).to_string()
+    type Parsed = Vec<Fish>;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        fish(inp)
+    }
+    fn pt1(&self, initial_fish: &Self::Parsed) -> Result<Answer, SolveError> {
+        Ok(solve(80, initial_fish.clone()).into())
     }
-    fn pt2(&self, inp: &str) -> String {
-        let initial_fish = fish(inp);
-        solve(256, initial_fish).to_string()
+    fn pt2(&self, initial_fish: &Self::Parsed) -> Result<Answer, SolveError> {
+        Ok(solve(256, initial_fish.clone()).into())
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Fish {
+/// A group of lanternfish sharing the same timer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fish {
     timer: i64,
     number: i64,
 }
@@ -33,7 +45,7 @@ fn solve(days: i64, mut fish: Vec<Fish>) -> i64 {
             .map(|fishy| Fish { timer: 8, number: fishy.number })
             .reduce(|a, b| Fish { timer: 8, number: a.number + b.number });
         for i in 0..fish.len() {
-            let mut fishy = fish.get_mut(i).unwrap();
+            let fishy = fish.get_mut(i).unwrap();
             let new_timer = fishy.timer - 1;
             let new_timer = if new_timer >= 0 { new_timer } else { 6 };
             fishy.timer = new_timer;
@@ -46,17 +58,13 @@ fn solve(days: i64, mut fish: Vec<Fish>) -> i64 {
     }
 }
 
-fn fish(inp: &str) -> Vec<Fish> {
+fn fish(inp: &str) -> Result<Vec<Fish>, SolveError> {
     inp.split(',')
-        .map(|v| v.parse::<i64>().unwrap())
-        .map(|v| Fish { timer: v, number: 1 })
+        .map(|v| parse::<i64>(DAY, inp, v))
+        .map(|v| v.map(|v| Fish { timer: v, number: 1 }))
         .collect()
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input06.txt").trim()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,38 +76,46 @@ mod tests {
     #[test]
     fn test_fish() {
         assert_eq!(
-            vec![3, 4, 3, 1, 2].iter().map(|v| Fish { timer: *v, number: 1 }).collect::<Vec<Fish>>(),
-            fish(example_input())
+            [3, 4, 3, 1, 2].iter().map(|v| Fish { timer: *v, number: 1 }).collect::<Vec<Fish>>(),
+            fish(example_input()).unwrap()
+        )
+    }
+
+    #[test]
+    fn test_fish_malformed() {
+        assert_eq!(
+            fish("3,4,3,,2").unwrap_err().to_string(),
+            "day 06, line 1, column 7: cannot parse integer from empty string ('')"
         )
     }
 
     #[test]
     fn test_solve_example_18_days() {
-        assert_eq!(solve(18, fish(example_input())), 26)
+        assert_eq!(solve(18, fish(example_input()).unwrap()), 26)
     }
 
     #[test]
     fn test_solve_example_80_days() {
-        assert_eq!(solve(80, fish(example_input())), 5934)
+        assert_eq!(solve(80, fish(example_input()).unwrap()), 5934)
     }
 
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "5934")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(5934)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "373378")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(373378)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "26984457539")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(26984457539)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "1682576647495")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(1682576647495)))
     }
 }
@@ -1,13 +1,55 @@
-use crate::problem::Solver;
+//! [Day 7: The Treachery of Whales](https://adventofcode.com/2021/day/7).
 
+use crate::problem::{Answer, parse, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 7;
+
+/// The solver of day 7, trying every position between the outermost crabs.
 pub struct Day {}
 
+/// Solves day 7 at the median for part 1, and next to the mean for part 2.
+pub struct Median {}
+
+puzzle!(
+    year: 2021,
+    day: DAY,
+    title: "The Treachery of Whales",
+    solver: Day {},
+    variants: ["median" => Median {}],
+    input: "input07.txt",
+);
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        align(inp, &FuelCost::Constant).to_string()
+    /// The crab positions, in ascending order.
+    type Parsed = Vec<i64>;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        coordinates(inp)
+    }
+    fn pt1(&self, coordinates: &Self::Parsed) -> Result<Answer, SolveError> {
+        Ok(align(coordinates, &FuelCost::Constant).into())
+    }
+    fn pt2(&self, coordinates: &Self::Parsed) -> Result<Answer, SolveError> {
+        Ok(align(coordinates, &FuelCost::Increasing).into())
+    }
+}
+
+impl Solver for Median {
+    /// The crab positions, in ascending order.
+    type Parsed = Vec<i64>;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        coordinates(inp)
+    }
+    fn pt1(&self, coordinates: &Self::Parsed) -> Result<Answer, SolveError> {
+        // Moving away from the median moves away from at least as many crabs as it moves towards.
+        Ok(fuel(coordinates, coordinates[coordinates.len() / 2], &FuelCost::Constant).into())
     }
-    fn pt2(&self, inp: &str) -> String {
-        align(inp, &FuelCost::Increasing).to_string()
+    fn pt2(&self, coordinates: &Self::Parsed) -> Result<Answer, SolveError> {
+        // The cheapest position is within half a step of the mean.
+        let mean = coordinates.iter().sum::<i64>().div_euclid(coordinates.len() as i64);
+        Ok((mean..=mean + 1).map(|candidate| fuel(coordinates, candidate, &FuelCost::Increasing)).min().unwrap().into())
     }
 }
 
@@ -24,29 +66,31 @@ fn fuel_between(a: i64, b: i64, fuel_cost: &FuelCost) -> i64 {
     }
 }
 
-fn align(inp: &str, fuel_cost: &FuelCost) -> i64 {
+fn coordinates(inp: &str) -> Result<Vec<i64>, SolveError> {
     let mut coordinates: Vec<i64> = inp
         .split(',')
-        .map(|v| v.parse::<i64>().unwrap())
-        .collect();
+        .map(|v| parse::<i64>(DAY, inp, v))
+        .collect::<Result<Vec<i64>, SolveError>>()?;
     coordinates.sort_unstable();
+    Ok(coordinates)
+}
+
+fn fuel(coordinates: &[i64], candidate: i64, fuel_cost: &FuelCost) -> i64 {
+    coordinates
+        .iter()
+        .map(|coordinate| fuel_between(*coordinate, candidate, fuel_cost))
+        .sum()
+}
+
+fn align(coordinates: &[i64], fuel_cost: &FuelCost) -> i64 {
     let coordinate_extremes: (i64, i64) = (*coordinates.first().unwrap(), *coordinates.last().unwrap());
 
     (coordinate_extremes.0..=coordinate_extremes.1)
-        .into_iter()
-        .map(|candidate|
-            coordinates
-                .iter()
-                .map(|coordinate| fuel_between(*coordinate, candidate, fuel_cost))
-                .sum())
+        .map(|candidate| fuel(coordinates, candidate, fuel_cost))
         .min()
         .unwrap()
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input07.txt").trim()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,12 +101,20 @@ mod tests {
 
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "37")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(37)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "351901")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(351901)))
+    }
+
+    #[test]
+    fn test_coordinates_malformed() {
+        assert_eq!(
+            coordinates("16,1,2,O").unwrap_err().to_string(),
+            "day 07, line 1, column 8: invalid digit found in string ('O')"
+        )
     }
 
     #[test]
@@ -115,11 +167,19 @@ mod tests {
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "168")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(168)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "101079875")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(101079875)))
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(Median {}.solve_pt1(example_input()), Ok(Answer::Integer(37)));
+        assert_eq!(Median {}.solve_pt2(example_input()), Ok(Answer::Integer(168)));
+        assert_eq!(Median {}.solve_pt1(input()), Ok(Answer::Integer(351901)));
+        assert_eq!(Median {}.solve_pt2(input()), Ok(Answer::Integer(101079875)));
     }
 }
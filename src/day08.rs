@@ -1,11 +1,25 @@
+//! [Day 8: Seven Segment Search](https://adventofcode.com/2021/day/8).
+
 use std::collections::HashMap;
-use crate::problem::Solver;
+use crate::problem::{Answer, split_once, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 8;
 
+/// The solver of day 8.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Seven Segment Search", solver: Day {}, input: "input08.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
+    /// The decoded output digits of each display.
+    type Parsed = Vec<Vec<u32>>;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
         decode(inp)
+    }
+    fn pt1(&self, outputs: &Self::Parsed) -> Result<Answer, SolveError> {
+        let count = outputs
             .iter()
             .map(|vec|
                 vec
@@ -13,17 +27,15 @@ impl Solver for Day {
                     .filter(|e| [1, 4, 7, 8].contains(e))
                     .count()
             )
-            .sum::<usize>()
-            .to_string()
+            .sum::<usize>();
+        Ok(count.into())
     }
-    fn pt2(&self, inp: &str) -> String {
-        decode(inp)
+    fn pt2(&self, outputs: &Self::Parsed) -> Result<Answer, SolveError> {
+        let sum = outputs
             .iter()
-            .map(|vec|
-                vec.iter().map(|d| d.to_string()).collect::<String>().parse::<u32>().unwrap()
-            )
-            .sum::<u32>()
-            .to_string()
+            .map(|vec| vec.iter().fold(0, |acc, digit| acc * 10 + digit))
+            .sum::<u32>();
+        Ok(sum.into())
     }
 }
 
@@ -62,24 +74,26 @@ fn resolve_digit(digits: &HashMap<u32, String>, input: &str) -> Option<u32> {
     }
 }
 
-fn decode(inp: &str) -> Vec<Vec<u32>> {
-    let entries: Vec<Vec<Vec<String>>> = inp
+fn decode(inp: &str) -> Result<Vec<Vec<u32>>, SolveError> {
+    let entries: Vec<(&str, Vec<Vec<String>>)> = inp
         .split('\n')
-        .map(|line|
-            line
-                .split(" | ")
+        .map(|line| {
+            let (inputs, outputs) = split_once(DAY, inp, line, " | ")?;
+            let entry = [inputs, outputs]
+                .iter()
                 .map(|entries| entries.split(' ').collect::<Vec<&str>>())
                 .map(|v| v.iter().map(|s| {
                     let mut chars: Vec<char> = s.chars().collect();
                     chars.sort_unstable();
                     chars.into_iter().collect::<String>()
                 }).collect())
-                .collect::<Vec<Vec<String>>>()
-        )
-        .collect();
+                .collect::<Vec<Vec<String>>>();
+            Ok((line, entry))
+        })
+        .collect::<Result<Vec<(&str, Vec<Vec<String>>)>, SolveError>>()?;
 
     let mut mapped_outputs: Vec<Vec<u32>> = vec![];
-    for entry in &entries {
+    for (line, entry) in &entries {
         let mut digits: HashMap<u32, String> = HashMap::new();
         let inputs = entry.first().unwrap();
         let outputs = entry.last().unwrap();
@@ -97,18 +111,17 @@ fn decode(inp: &str) -> Vec<Vec<u32>> {
         let mapped_output: Vec<u32> = outputs
             .iter()
             .map(|output| {
-                digits.iter().find(|e| **e.1 == **output).unwrap().0
+                digits
+                    .iter()
+                    .find(|e| **e.1 == **output)
+                    .map(|e| *e.0)
+                    .ok_or_else(|| SolveError::parse(DAY, inp, line, format!("cannot decode output '{}'", output)))
             })
-            .copied()
-            .collect();
+            .collect::<Result<Vec<u32>, SolveError>>()?;
         mapped_outputs.push(mapped_output);
     }
 
-    mapped_outputs
-}
-
-pub(crate) fn input() -> &'static str {
-    include_str!("input08.txt").trim()
+    Ok(mapped_outputs)
 }
 
 #[cfg(test)]
@@ -128,23 +141,35 @@ egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
 gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce"
     }
 
+    #[test]
+    fn test_decode_malformed() {
+        assert_eq!(
+            decode("be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb fdgacbe cefdb cefbgd gcbe").unwrap_err().to_string(),
+            "day 08, line 1, column 1: expected ' | ' ('be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb fdgacbe cefdb cefbgd gcbe')"
+        );
+        assert_eq!(
+            decode("be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe xyz").unwrap_err().to_string(),
+            "day 08, line 1, column 1: cannot decode output 'xyz' ('be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe xyz')"
+        );
+    }
+
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "26")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(26)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "452")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(452)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "61229")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(61229)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "1096964")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(1096964)))
     }
 }
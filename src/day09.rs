@@ -1,24 +1,40 @@
-use crate::problem::Solver;
+//! [Day 9: Smoke Basin](https://adventofcode.com/2021/day/9).
 
+use crate::problem::{Answer, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 9;
+
+/// The solver of day 9.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Smoke Basin", solver: Day {}, input: "input09.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let map = map(inp);
-        let low_points = low_points(&map);
-        low_points.iter().map(|p| map.values[*p as usize] + 1).sum::<i16>().to_string()
+    type Parsed = Map;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        map(inp)
     }
-    fn pt2(&self, inp: &str) -> String {
-        let basins = basins(&map(inp));
+    fn pt1(&self, map: &Self::Parsed) -> Result<Answer, SolveError> {
+        let low_points = low_points(map);
+        Ok(low_points.iter().map(|p| map.values[*p as usize] + 1).sum::<i16>().into())
+    }
+    fn pt2(&self, map: &Self::Parsed) -> Result<Answer, SolveError> {
+        let basins = basins(map);
         let mut sizes: Vec<usize> = basins.iter().map(|basin| basin.len()).collect();
+        if sizes.len() < 3 {
+            return Err(SolveError::unsolvable(DAY, format!("expected at least 3 basins, found {}", sizes.len())));
+        }
         sizes.sort_unstable();
         sizes.reverse();
-        sizes[..3].iter().product::<usize>().to_string()
+        Ok(sizes[..3].iter().product::<usize>().into())
     }
 }
 
+/// The heightmap of the cave floor.
 #[derive(Debug, Eq, PartialEq)]
-struct Map {
+pub struct Map {
     values: Vec<i16>,
     width: i16,
     height: i16,
@@ -49,7 +65,7 @@ fn adjacent(row: i16, column: i16, map: &Map) -> Vec<i16> {
 fn basin(map: &Map, point: i16) -> Vec<i16> {
     let val = map.values[point as usize];
     let (row, column) = row_column(map, &(point as usize));
-    let mut basin = vec![
+    let mut basin = [
         vec![point],
         adjacent(row, column, map)
             .iter()
@@ -87,20 +103,30 @@ fn row_column(map: &Map, point: &usize) -> (i16, i16) {
     (*point as i16 / map.width, *point as i16 % map.width)
 }
 
-fn map(inp: &str) -> Map {
+fn map(inp: &str) -> Result<Map, SolveError> {
     let lines: Vec<&str> = inp.split('\n').collect();
     let height = lines.len() as i16;
     let width = lines.first().unwrap().len() as i16;
-    let values: Vec<i16> = lines.iter().flat_map(|s| s.chars().map(|c| c.to_string().parse::<i16>().unwrap()).collect::<Vec<i16>>()).collect();
-    Map {
+    if width == 0 {
+        return Err(SolveError::parse(DAY, inp, lines[0], "expected a row of digits"));
+    }
+    let mut values: Vec<i16> = vec![];
+    for line in &lines {
+        if line.len() != width as usize {
+            return Err(SolveError::parse(DAY, inp, line, format!("expected {} digits", width)));
+        }
+        for (i, c) in line.char_indices() {
+            let digit = c
+                .to_digit(10)
+                .ok_or_else(|| SolveError::parse(DAY, inp, &line[i..i + c.len_utf8()], "expected a digit"))?;
+            values.push(digit as i16);
+        }
+    }
+    Ok(Map {
         width,
         height,
         values,
-    }
-}
-
-pub(crate) fn input() -> &'static str {
-    include_str!("input09.txt").trim()
+    })
 }
 
 #[cfg(test)]
@@ -125,20 +151,20 @@ mod tests {
                 vec![12, 13, 14, 21, 22, 23, 24, 25, 30, 31, 32, 33, 34, 41],
                 vec![27, 36, 37, 38, 45, 46, 47, 48, 49],
             ],
-            basins(&map(example_input()))
+            basins(&map(example_input()).unwrap())
         );
     }
 
     #[test]
     fn test_adjacent_example() {
-        assert_eq!(vec![1, 10], adjacent(0, 0, &map(example_input())));
-        assert_eq!(vec![2, 11, 0], adjacent(0, 1, &map(example_input())));
+        assert_eq!(vec![1, 10], adjacent(0, 0, &map(example_input()).unwrap()));
+        assert_eq!(vec![2, 11, 0], adjacent(0, 1, &map(example_input()).unwrap()));
     }
 
     #[test]
     fn test_low_points_example() {
         assert_eq!(
-            low_points(&map(example_input())),
+            low_points(&map(example_input()).unwrap()),
             vec![1, 9, 22, 46]
         )
     }
@@ -146,7 +172,7 @@ mod tests {
     #[test]
     fn test_map_example() {
         assert_eq!(
-            map(example_input()),
+            map(example_input()).unwrap(),
             Map {
                 height: 5,
                 width: 10,
@@ -161,23 +187,37 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_map_malformed() {
+        assert_eq!(
+            map("2199\n39x7").unwrap_err().to_string(),
+            "day 09, line 2, column 3: expected a digit ('x')"
+        );
+        assert_eq!(
+            map("2199\n398").unwrap_err().to_string(),
+            "day 09, line 2, column 1: expected 4 digits ('398')"
+        );
+        assert_eq!(map("").unwrap_err().to_string(), "day 09, line 1, column 1: expected a row of digits ('')");
+        assert!(map("\n2199").is_err());
+    }
+
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "15")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(15)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "631")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(631)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "1134")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(1134)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "821560")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(821560)))
     }
 }
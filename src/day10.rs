@@ -1,26 +1,39 @@
+//! [Day 10: Syntax Scoring](https://adventofcode.com/2021/day/10).
+
 use std::collections::HashMap;
-use crate::problem::Solver;
+use crate::problem::{Answer, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 10;
 
+/// The solver of day 10.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Syntax Scoring", solver: Day {}, input: "input10.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
+    type Parsed = Vec<String>;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        Ok(lines(inp)?.into_iter().map(|line| line.to_string()).collect())
+    }
+    fn pt1(&self, lines: &Self::Parsed) -> Result<Answer, SolveError> {
         let score_mapping: HashMap<char, u64> = [(')', 3), (']', 57), ('}', 1197), ('>', 25137)].into();
 
-        inp
-            .split('\n')
+        let score = lines
+            .iter()
             .map(|line| (line, chunks(line)))
             .filter(|(line, (chunks, _))| line.len() > chunks.len())
             .map(|(_, (chunks, _))| chunks.chars().last().unwrap())
             .map(|character| *score_mapping.get(&character).unwrap())
-            .sum::<u64>()
-            .to_string()
+            .sum::<u64>();
+        Ok(score.into())
     }
-    fn pt2(&self, inp: &str) -> String {
+    fn pt2(&self, lines: &Self::Parsed) -> Result<Answer, SolveError> {
         let score_mapping: HashMap<char, u64> = [(')', 1), (']', 2), ('}', 3), ('>', 4)].into();
 
-        let mut scores: Vec<u64> = inp
-            .split('\n')
+        let mut scores: Vec<u64> = lines
+            .iter()
             .map(|line| (line, chunks(line)))
             .filter(|(line, (chunks, _))| chunks.len() == line.len())
             .map(|(_, (_, completion))| completion)
@@ -29,11 +42,29 @@ impl Solver for Day {
                 .map(|character| *score_mapping.get(character).unwrap())
                 .fold(0, |acc, score| acc * 5 + score))
             .collect();
+        if scores.is_empty() {
+            return Err(SolveError::unsolvable(DAY, "no incomplete lines"));
+        }
         scores.sort_unstable();
-        scores[scores.len() / 2].to_string()
+        Ok(scores[scores.len() / 2].into())
     }
 }
 
+fn lines(inp: &str) -> Result<Vec<&str>, SolveError> {
+    inp
+        .split('\n')
+        .map(|line| {
+            if line.is_empty() {
+                return Err(SolveError::parse(DAY, inp, line, "expected a line of brackets"));
+            }
+            match line.char_indices().find(|(_, c)| !"()[]{}<>".contains(*c)) {
+                Some((i, c)) => Err(SolveError::parse(DAY, inp, &line[i..i + c.len_utf8()], "expected a bracket")),
+                None => Ok(line),
+            }
+        })
+        .collect()
+}
+
 fn chunks(line: &str) -> (&str, Vec<char>) {
     let mut chunks_starts = vec![];
     let markers: HashMap<char, char> = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')].into();
@@ -45,9 +76,8 @@ fn chunks(line: &str) -> (&str, Vec<char>) {
         if markers.contains_key(&char) {
             chunks_starts.push(char);
         } else if markers.values().any(|c| *c == char) {
-            let chunk_start = chunks_starts.pop().unwrap();
-            let expected = *markers.get(&chunk_start).unwrap();
-            if char != expected {
+            let expected = chunks_starts.pop().map(|chunk_start| *markers.get(&chunk_start).unwrap());
+            if expected != Some(char) {
                 break;
             }
         }
@@ -58,10 +88,6 @@ fn chunks(line: &str) -> (&str, Vec<char>) {
     )
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input10.txt").trim()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,25 +149,40 @@ mod tests {
             chunks("<{([([[(<>()){}]>(<<{{"),
             ("<{([([[(<>()){}]>", vec![')', ']', ')', '}', '>'])
         );
+
+        assert_eq!(
+            chunks("()]"),
+            ("()]", vec![])
+        );
+    }
+
+    #[test]
+    fn test_lines_malformed() {
+        assert_eq!(
+            lines("[({(<(())[]>[[{[]{<()<>>\n[(()[<>])]({[<{<<[]>>(x").unwrap_err().to_string(),
+            "day 10, line 2, column 23: expected a bracket ('x')"
+        );
+        assert_eq!(lines("").unwrap_err().to_string(), "day 10, line 1, column 1: expected a line of brackets ('')");
+        assert!(lines("()\n\n[]").is_err());
     }
 
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "26397")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(26397)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "339411")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(339411)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "288957")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(288957)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "2289754624")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(2289754624)))
     }
 }
@@ -1,35 +1,41 @@
+//! [Day 11: Dumbo Octopus](https://adventofcode.com/2021/day/11).
+
 use std::cmp::{max, min};
 use std::fmt::{Display, Formatter};
-use crate::problem::Solver;
+use crate::problem::{Answer, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 11;
 
+/// The solver of day 11.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Dumbo Octopus", solver: Day {}, input: "input11.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
+    type Parsed = Grid;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
         Grid::new(inp)
-            .simulate(100)
-            .octopuses
-            .iter()
-            .map(|octopus| octopus.flashes)
-            .sum::<u64>()
-            .to_string()
     }
-    fn pt2(&self, inp: &str) -> String {
-        let mut grid = Grid::new(inp);
+    fn pt1(&self, grid: &Self::Parsed) -> Result<Answer, SolveError> {
+        Ok(grid.clone().simulate(100).flashes().into())
+    }
+    fn pt2(&self, grid: &Self::Parsed) -> Result<Answer, SolveError> {
+        let mut grid = grid.clone();
         let mut i = 0;
         while !grid.zeroed() {
             grid.simulate(1);
             i += 1;
         }
-        i.to_string()
+        Ok(i.into())
+    }
+    fn visualize(&self, grid: &Self::Parsed) -> Option<String> {
+        Some(grid.to_string())
     }
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input11.txt").trim()
-}
-
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 struct Octopus {
     energy: u8,
     flashes: u64,
@@ -69,8 +75,9 @@ impl Octopus {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Grid {
+/// The grid of dumbo octopuses and their energy levels.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grid {
     octopuses: Vec<Octopus>,
     width: usize,
     height: usize,
@@ -91,30 +98,47 @@ impl Display for Grid {
 }
 
 impl Grid {
-    fn new(inp: &str) -> Grid {
+    fn new(inp: &str) -> Result<Grid, SolveError> {
         let lines: Vec<&str> = inp.split('\n').collect();
         let height = lines.len();
         let width = lines.first().unwrap().len();
-        let energy_levels: Vec<u8> = lines
-            .iter()
-            .flat_map(|line| line.chars().map(|c| c.to_string().parse::<u8>().unwrap()))
-            .collect();
+        if width == 0 {
+            return Err(SolveError::parse(DAY, inp, lines[0], "expected a row of digits"));
+        }
+        let mut energy_levels: Vec<u8> = vec![];
+        for line in &lines {
+            if line.len() != width {
+                return Err(SolveError::parse(DAY, inp, line, format!("expected {} digits", width)));
+            }
+            for (i, c) in line.char_indices() {
+                let energy = c
+                    .to_digit(10)
+                    .ok_or_else(|| SolveError::parse(DAY, inp, &line[i..i + c.len_utf8()], "expected a digit"))?;
+                energy_levels.push(energy as u8);
+            }
+        }
         let octopuses: Vec<Octopus> = energy_levels
             .iter()
             .map(|energy| Octopus::new(*energy))
             .collect();
-        Grid {
+        Ok(Grid {
             octopuses,
             width,
             height,
-        }
+        })
     }
 
     fn zeroed(&self) -> bool {
         self.octopuses.iter().all(|o| o.energy == 0)
     }
 
-    fn simulate(&mut self, steps: u8) -> &Grid {
+    /// The number of flashes of all octopuses so far.
+    pub fn flashes(&self) -> u64 {
+        self.octopuses.iter().map(|octopus| octopus.flashes).sum()
+    }
+
+    /// Charges every octopus `steps` times, letting those with enough energy flash.
+    pub fn simulate(&mut self, steps: u8) -> &Grid {
         let mut steps = steps;
         while steps > 0 {
             for i in 0..self.octopuses.len() {
@@ -128,6 +152,7 @@ impl Grid {
                 let octopus = self.octopuses.get_mut(i).unwrap();
                 octopus.reset();
             }
+            tracing::debug!(flashed = self.octopuses.iter().filter(|octopus| octopus.energy == 0).count(), "stepped");
             steps -= 1
         }
         self
@@ -182,7 +207,7 @@ mod tests {
 
     #[test]
     fn test_simulate() {
-        let mut grid = Grid::new(example_input());
+        let mut grid = Grid::new(example_input()).unwrap();
         assert_eq!(
             Grid {
                 octopuses: example_input()
@@ -246,23 +271,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_grid_new_malformed() {
+        assert_eq!(
+            Grid::new("5483\n27-5").unwrap_err().to_string(),
+            "day 11, line 2, column 3: expected a digit ('-')"
+        );
+        assert_eq!(
+            Grid::new("5483\n27458").unwrap_err().to_string(),
+            "day 11, line 2, column 1: expected 4 digits ('27458')"
+        );
+        assert_eq!(Grid::new("").unwrap_err().to_string(), "day 11, line 1, column 1: expected a row of digits ('')");
+    }
+
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "1656")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(1656)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "1599")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(1599)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "195")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(195)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "418")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(418)))
     }
 }
@@ -1,18 +1,53 @@
-use crate::problem::Solver;
+//! [Day 12: Passage Pathing](https://adventofcode.com/2021/day/12).
 
+use std::collections::HashMap;
+
+use crate::problem::{Answer, split_once, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 12;
+
+/// The solver of day 12, enumerating every path.
 pub struct Day {}
 
+/// Solves day 12 by counting the paths, without building them.
+pub struct Counting {}
+
+puzzle!(
+    year: 2021,
+    day: DAY,
+    title: "Passage Pathing",
+    solver: Day {},
+    variants: ["counting" => Counting {}],
+    input: "input12.txt",
+);
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        Map::new(inp, false).go((false, vec![]), "start", "end").len().to_string()
+    type Parsed = Map;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        Map::new(inp)
     }
-    fn pt2(&self, inp: &str) -> String {
-        Map::new(inp, true).go((false, vec![]), "start", "end").len().to_string()
+    fn pt1(&self, map: &Self::Parsed) -> Result<Answer, SolveError> {
+        Ok(map.go((false, vec![]), "start", "end", false).len().into())
+    }
+    fn pt2(&self, map: &Self::Parsed) -> Result<Answer, SolveError> {
+        Ok(map.go((false, vec![]), "start", "end", true).len().into())
     }
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input12.txt").trim()
+impl Solver for Counting {
+    type Parsed = Map;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        Map::new(inp)
+    }
+    fn pt1(&self, map: &Self::Parsed) -> Result<Answer, SolveError> {
+        Ok(map.count("start", "end", false)?.into())
+    }
+    fn pt2(&self, map: &Self::Parsed) -> Result<Answer, SolveError> {
+        Ok(map.count("start", "end", true)?.into())
+    }
 }
 
 struct Cave {
@@ -28,23 +63,34 @@ impl Cave {
     }
 }
 
-struct Map {
+/// The cave system, as connections between caves.
+pub struct Map {
     caves: Vec<Cave>,
-    allow_duplicate: bool,
 }
 
 impl Map {
-    fn new(inp: &str, allow_duplicate: bool) -> Map {
-        let mut map = Map { caves: vec![], allow_duplicate };
+    fn new(inp: &str) -> Result<Map, SolveError> {
+        let mut map = Map { caves: vec![] };
         for line in inp.split('\n') {
-            let from_to = line.split('-').collect::<Vec<&str>>();
-            let (from, to) = (from_to.first().unwrap(), from_to.last().unwrap());
+            let (from, to) = split_once(DAY, inp, line, "-")?;
+            for cave in [from, to] {
+                if cave.is_empty() || !cave.chars().all(|c| c.is_ascii_alphabetic()) {
+                    return Err(SolveError::parse(DAY, inp, cave, "expected a cave name"));
+                }
+            }
+            // Paths could go back and forth between two big caves forever.
+            if [from, to].iter().all(|cave| cave.chars().all(char::is_uppercase)) {
+                return Err(SolveError::parse(DAY, inp, line, "two big caves are connected, so there are endless paths"));
+            }
             map.add(from.to_string());
             map.add(to.to_string());
             map.connect(from.to_string(), to.to_string());
             map.connect(to.to_string(), from.to_string());
         }
-        map
+        if !map.caves.iter().any(|c| c.name == "start") {
+            return Err(SolveError::unsolvable(DAY, "there is no 'start' cave"));
+        }
+        Ok(map)
     }
 
     fn add(&mut self, cave_name: String) {
@@ -58,17 +104,18 @@ impl Map {
         first.connect(cave_2);
     }
 
-    /// Get all possible paths from `from` to `to`.
-    fn go(&self, path: (bool, Vec<String>), from: &str, to: &str) -> Vec<(bool, Vec<String>)> {
-        let path = (path.0, vec![path.1, vec![from.to_string()]].concat());
+    /// Get all possible paths from `from` to `to`, optionally visiting a single small cave twice.
+    fn go(&self, path: (bool, Vec<String>), from: &str, to: &str, allow_duplicate: bool) -> Vec<(bool, Vec<String>)> {
+        let path = (path.0, [path.1, vec![from.to_string()]].concat());
         if from == to {
+            tracing::debug!(path = %path.1.join(","), twice = path.0, "found");
             vec![path]
         } else {
             let from_cave = self.caves.iter().find(|c| c.name == from).unwrap();
             from_cave.connections
                 .iter()
                 .filter(|neighbor| *neighbor != "start")
-                .map(|neighbor| {
+                .filter_map(|neighbor| {
                     let is_big = neighbor.chars().all(char::is_uppercase);
                     if is_big {
                         Option::Some((path.clone(), neighbor))
@@ -76,18 +123,75 @@ impl Map {
                         let not_visited = !path.1.contains(neighbor);
                         if not_visited {
                             Option::Some((path.clone(), neighbor))
-                        } else if self.allow_duplicate && !path.0 {
+                        } else if allow_duplicate && !path.0 {
+                            tracing::debug!(cave = %neighbor, after = %path.1.join(","), "visiting twice");
                             Option::Some(((true, path.1.clone()), neighbor))
                         } else {
                             Option::None
                         }
                     }
                 })
-                .flatten()
-                .flat_map(|(path, neighbor)| self.go(path, neighbor, to))
+                .flat_map(|(path, neighbor)| self.go(path, neighbor, to, allow_duplicate))
                 .collect::<Vec<(bool, Vec<String>)>>()
         }
     }
+
+    /// Count the paths from `from` to `to`, with the same rules as [`Map::go`].
+    fn count(&self, from: &str, to: &str, allow_duplicate: bool) -> Result<usize, SolveError> {
+        // The visited small caves are a bit set.
+        if self.caves.len() > 64 {
+            return Err(SolveError::unsolvable(DAY, "too many caves to count the paths of"));
+        }
+        let index = |name: &str| self.caves.iter().position(|c| c.name == name);
+        let (from, to) = match (index(from), index(to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Ok(0),
+        };
+        let mut counter = Counter {
+            connections: self.caves
+                .iter()
+                .map(|cave| cave.connections.iter().filter(|c| *c != "start").filter_map(|c| index(c)).collect())
+                .collect(),
+            small: self.caves.iter().map(|cave| !cave.name.chars().all(char::is_uppercase)).collect(),
+            to,
+            counts: HashMap::new(),
+        };
+        let count = counter.count(from, 1 << from, !allow_duplicate);
+        tracing::debug!(count, remembered = counter.counts.len(), "counted");
+        Ok(count)
+    }
+}
+
+/// Counts paths through caves by index, remembering the count from every cave and set of visited small caves.
+struct Counter {
+    connections: Vec<Vec<usize>>,
+    small: Vec<bool>,
+    to: usize,
+    counts: HashMap<(usize, u64, bool), usize>,
+}
+
+impl Counter {
+    fn count(&mut self, from: usize, visited: u64, duplicated: bool) -> usize {
+        if from == self.to {
+            return 1;
+        }
+        if let Some(count) = self.counts.get(&(from, visited, duplicated)) {
+            return *count;
+        }
+        let mut count = 0;
+        for i in 0..self.connections[from].len() {
+            let neighbor = self.connections[from][i];
+            if !self.small[neighbor] {
+                count += self.count(neighbor, visited, duplicated);
+            } else if visited & (1 << neighbor) == 0 {
+                count += self.count(neighbor, visited | (1 << neighbor), duplicated);
+            } else if !duplicated {
+                count += self.count(neighbor, visited, true);
+            }
+        }
+        self.counts.insert((from, visited, duplicated), count);
+        count
+    }
 }
 
 #[cfg(test)]
@@ -141,43 +245,74 @@ pj-fs
 start-RW".trim()
     }
 
+    #[test]
+    fn test_map_new_malformed() {
+        assert_eq!(
+            Map::new("start-A\nA=end").err().unwrap().to_string(),
+            "day 12, line 2, column 1: expected '-' ('A=end')"
+        );
+        assert_eq!(
+            Map::new("start-A\nA-3").err().unwrap().to_string(),
+            "day 12, line 2, column 3: expected a cave name ('3')"
+        );
+        assert_eq!(
+            Map::new("A-end").err().unwrap().to_string(),
+            "day 12: there is no 'start' cave"
+        );
+        assert_eq!(
+            Map::new("start-A\nA-B\nB-end").err().unwrap().to_string(),
+            "day 12, line 2, column 1: two big caves are connected, so there are endless paths ('A-B')"
+        );
+    }
+
     #[test]
     fn test_pt1_example_1() {
-        assert_eq!(Day {}.pt1(example_input_1()), "10")
+        assert_eq!(Day {}.solve_pt1(example_input_1()), Ok(Answer::Integer(10)))
     }
 
     #[test]
     fn test_pt1_example_2() {
-        assert_eq!(Day {}.pt1(example_input_2()), "19")
+        assert_eq!(Day {}.solve_pt1(example_input_2()), Ok(Answer::Integer(19)))
     }
 
     #[test]
     fn test_pt1_example_3() {
-        assert_eq!(Day {}.pt1(example_input_3()), "226")
+        assert_eq!(Day {}.solve_pt1(example_input_3()), Ok(Answer::Integer(226)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "5874")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(5874)))
     }
 
     #[test]
     fn test_pt2_example_1() {
-        assert_eq!(Day {}.pt2(example_input_1()), "36")
+        assert_eq!(Day {}.solve_pt2(example_input_1()), Ok(Answer::Integer(36)))
     }
 
     #[test]
     fn test_pt2_example_2() {
-        assert_eq!(Day {}.pt2(example_input_2()), "103")
+        assert_eq!(Day {}.solve_pt2(example_input_2()), Ok(Answer::Integer(103)))
     }
 
     #[test]
     fn test_pt2_example_3() {
-        assert_eq!(Day {}.pt2(example_input_3()), "3509")
+        assert_eq!(Day {}.solve_pt2(example_input_3()), Ok(Answer::Integer(3509)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "153592")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(153592)))
+    }
+
+    #[test]
+    fn test_counting() {
+        for (example, pt1, pt2) in [(example_input_1(), 10, 36), (example_input_2(), 19, 103), (example_input_3(), 226, 3509)] {
+            assert_eq!(Counting {}.solve_pt1(example), Ok(Answer::Integer(pt1)));
+            assert_eq!(Counting {}.solve_pt2(example), Ok(Answer::Integer(pt2)));
+        }
+        assert_eq!(Counting {}.solve_pt1(input()), Ok(Answer::Integer(5874)));
+        assert_eq!(Counting {}.solve_pt2(input()), Ok(Answer::Integer(153592)));
+        assert_eq!(Counting {}.solve_pt1("start-A"), Ok(Answer::Integer(0)));
     }
 }
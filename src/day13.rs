@@ -1,39 +1,62 @@
-use std::cmp::max;
+//! [Day 13: Transparent Origami](https://adventofcode.com/2021/day/13).
+
+use std::cmp::{max, min};
 use std::fmt::{Display, Formatter};
-use crate::problem::Solver;
+use crate::problem::{Answer, parse, split_once, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 13;
+/// The most positions of the paper that are drawn, far more than the letters of the code need.
+const MAX_DRAWN: usize = 1 << 22;
 
+/// The solver of day 13.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Transparent Origami", solver: Day {}, input: "input13.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let mut manual = Manual::new(inp);
+    type Parsed = Manual;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        Manual::new(inp)
+    }
+    fn pt1(&self, manual: &Self::Parsed) -> Result<Answer, SolveError> {
+        let mut manual = manual.clone();
+        if manual.folds.is_empty() {
+            return Err(SolveError::unsolvable(DAY, "there are no fold instructions"));
+        }
         manual.fold();
-        manual.coordinates.len().to_string()
+        Ok(manual.coordinates.len().into())
     }
-    fn pt2(&self, inp: &str) -> String {
-        let mut manual = Manual::new(inp);
-        while !manual.folds.is_empty() {
-            manual.fold();
+    fn pt2(&self, manual: &Self::Parsed) -> Result<Answer, SolveError> {
+        let mut manual = manual.clone();
+        while manual.fold().is_some() {}
+        let (width, height) = manual.size();
+        if width.checked_mul(height).map(|drawn| drawn > MAX_DRAWN).unwrap_or(true) {
+            return Err(SolveError::unsolvable(DAY, format!("the folded paper is too large to read ({}x{})", width, height)));
         }
-        format!("\n{}", manual)
+        Ok(Answer::image(&manual.to_string()))
+    }
+    fn visualize(&self, manual: &Self::Parsed) -> Option<String> {
+        // The unfolded paper is too large to look at.
+        let mut manual = manual.clone();
+        while manual.fold().is_some() {}
+        let (width, height) = manual.size();
+        width.checked_mul(height).filter(|drawn| *drawn <= MAX_DRAWN).map(|_| manual.to_string())
     }
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input13.txt").trim()
-}
-
-#[derive(Eq, PartialEq)]
-struct Manual {
+/// The transparent paper of the manual: its dots and the folds still to make.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Manual {
     coordinates: Vec<(i16, i16)>,
     folds: Vec<(u8, i16)>,
 }
 
 impl Display for Manual {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let width = 1 + self.coordinates.iter().fold(0_usize, |a, b| max(a, b.0 as usize));
-        let height = 1 + self.coordinates.iter().fold(0_usize, |a, b| max(a, b.1 as usize));
-        let mut map = vec!['.'; width * height];
+        let (width, height) = self.size();
+        let mut map = vec!['.'; width.checked_mul(height).ok_or(std::fmt::Error)?];
 
         for coordinate in &self.coordinates {
             let (x, y) = coordinate;
@@ -52,24 +75,65 @@ impl Display for Manual {
 }
 
 impl Manual {
-    fn new(inp: &str) -> Manual {
-        let (coordinates, folds) = inp.split_once("\n\n").unwrap();
+    /// Parses the dots and folds, rejecting negative positions and folds that would move dots off the paper.
+    fn new(inp: &str) -> Result<Manual, SolveError> {
+        let (coordinates, folds) = split_once(DAY, inp, inp, "\n\n")?;
         let folds = folds
             .lines()
-            .map(|line| line.trim_start_matches("fold along ").split_once('=').unwrap())
-            .map(|(xy, i)| (xy.as_bytes()[0], i.parse::<i16>().unwrap()))
-            .collect::<Vec<(u8, i16)>>();
+            .map(|line| {
+                let (xy, i) = split_once(DAY, inp, line.trim_start_matches("fold along "), "=")?;
+                let xy = match xy {
+                    "x" => b'x',
+                    "y" => b'y',
+                    _ => return Err(SolveError::parse(DAY, inp, xy, "expected 'x' or 'y'")),
+                };
+                Ok((xy, i))
+            })
+            .collect::<Result<Vec<(u8, &str)>, SolveError>>()?;
         let coordinates = coordinates
             .lines()
-            .map(|line| line.split_once(',').unwrap())
-            .map(|(x, y)| (x.parse::<i16>().unwrap(), y.parse::<i16>().unwrap()))
-            .collect::<Vec<(i16, i16)>>();
+            .map(|line| {
+                let (x, y) = split_once(DAY, inp, line, ",")?;
+                Ok((position(inp, x)?, position(inp, y)?))
+            })
+            .collect::<Result<Vec<(i16, i16)>, SolveError>>()?;
+
+        // The furthest a dot can be from the folds made so far, in both directions. A fold mirrors the dots
+        // beyond its line, which must not take them past the edge of the paper.
+        let mut furthest = [
+            coordinates.iter().map(|(x, _)| *x).max().unwrap_or(0),
+            coordinates.iter().map(|(_, y)| *y).max().unwrap_or(0),
+        ];
+        let folds = folds
+            .into_iter()
+            .map(|(xy, i)| {
+                let line = position(inp, i)?;
+                let axis = if xy == b'x' { 0 } else { 1 };
+                if i32::from(furthest[axis]) > 2 * i32::from(line) {
+                    return Err(SolveError::parse(DAY, inp, i, format!("folds dots off the paper, which reach {}", furthest[axis])));
+                }
+                furthest[axis] = min(furthest[axis], line - 1);
+                Ok((xy, line))
+            })
+            .collect::<Result<Vec<(u8, i16)>, SolveError>>()?;
+
+        Ok(Manual { coordinates, folds })
+    }
 
-        Manual { coordinates, folds }
+    /// The width and height of the paper up to its furthest dots.
+    fn size(&self) -> (usize, usize) {
+        let width = 1 + self.coordinates.iter().fold(0_usize, |a, b| max(a, b.0 as usize));
+        let height = 1 + self.coordinates.iter().fold(0_usize, |a, b| max(a, b.1 as usize));
+        (width, height)
     }
-    fn fold(&mut self) {
-        let fold = self.folds.remove(0);
-        let (direction, i) = fold;
+
+    /// Makes the next fold, returning its direction, `x` or `y`, and line. Returns `None` when there are no
+    /// folds left.
+    pub fn fold(&mut self) -> Option<(char, i16)> {
+        if self.folds.is_empty() {
+            return None;
+        }
+        let (direction, i) = self.folds.remove(0);
         let mut new_coordinates: Vec<(i16, i16)> = self
             .coordinates
             .iter()
@@ -86,8 +150,19 @@ impl Manual {
             .collect();
         new_coordinates.sort_unstable();
         new_coordinates.dedup();
+        tracing::debug!(along = %(direction as char), line = i, dots = new_coordinates.len(), "folded");
         self.coordinates = new_coordinates;
+        Some((direction as char, i))
+    }
+}
+
+/// Parses a position on the paper, which can't be negative.
+fn position(inp: &str, text: &str) -> Result<i16, SolveError> {
+    let position = parse::<i16>(DAY, inp, text)?;
+    if position < 0 {
+        return Err(SolveError::parse(DAY, inp, text, "expected a position on the paper"));
     }
+    Ok(position)
 }
 
 #[cfg(test)]
@@ -119,36 +194,91 @@ fold along y=7
 fold along x=5".trim()
     }
 
+    #[test]
+    fn test_manual_new_malformed() {
+        assert_eq!(
+            Manual::new("6,10\n0;14\n\nfold along y=7").err().unwrap().to_string(),
+            "day 13, line 2, column 1: expected ',' ('0;14')"
+        );
+        assert_eq!(
+            Manual::new("6,10\n0,14\n\nfold along y=7\nfold along z=5").err().unwrap().to_string(),
+            "day 13, line 5, column 12: expected 'x' or 'y' ('z')"
+        );
+        assert_eq!(
+            Manual::new("6,10\n0,14\n\nfold along y=seven").err().unwrap().to_string(),
+            "day 13, line 4, column 14: invalid digit found in string ('seven')"
+        );
+        assert_eq!(
+            Manual::new("6,-10\n0,14\n\nfold along y=7").err().unwrap().to_string(),
+            "day 13, line 1, column 3: expected a position on the paper ('-10')"
+        );
+        assert_eq!(
+            Manual::new("0,0\n32767,32767\n\nfold along y=40000").err().unwrap().to_string(),
+            "day 13, line 4, column 14: number too large to fit in target type ('40000')"
+        );
+        assert_eq!(
+            Manual::new("6,10\n0,14\n\nfold along y=7\nfold along x=2").err().unwrap().to_string(),
+            "day 13, line 5, column 14: folds dots off the paper, which reach 6 ('2')"
+        );
+        assert_eq!(
+            Manual::new("0,0\n32767,32767\n\nfold along y=16383").err().unwrap().to_string(),
+            "day 13, line 4, column 14: folds dots off the paper, which reach 32767 ('16383')"
+        );
+    }
+
+    #[test]
+    fn test_pt2_too_large() {
+        assert_eq!(
+            Day {}.solve_pt2("0,0\n32767,32767\n\nfold along y=20000"),
+            Err(SolveError::unsolvable(DAY, "the folded paper is too large to read (32768x7234)"))
+        );
+    }
+
+    #[test]
+    fn test_fold() {
+        let mut manual = Manual::new(example_input()).unwrap();
+        assert_eq!(manual.fold(), Some(('y', 7)));
+        assert_eq!(manual.fold(), Some(('x', 5)));
+        assert_eq!(manual.fold(), None);
+        assert_eq!(manual.coordinates.len(), 16);
+    }
+
+    #[test]
+    fn test_visualize() {
+        let manual = Day {}.parse(example_input()).unwrap();
+        assert_eq!(Day {}.visualize(&manual), Some("#####\n#...#\n#...#\n#...#\n#####".to_string()));
+    }
+
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "17")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(17)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "693")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(693)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::image("
 #####
 #...#
 #...#
 #...#
 #####
-".trim_end())
+".trim())))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::image("
 #..#..##..#....####.###...##..####.#..#
 #..#.#..#.#.......#.#..#.#..#....#.#..#
 #..#.#....#......#..#..#.#..#...#..#..#
 #..#.#....#.....#...###..####..#...#..#
 #..#.#..#.#....#....#.#..#..#.#....#..#
 .##...##..####.####.#..#.#..#.####..##.
-".trim_end())
+".trim())))
     }
 }
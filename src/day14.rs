@@ -1,58 +1,93 @@
+//! [Day 14: Extended Polymerization](https://adventofcode.com/2021/day/14).
+
 use std::collections::HashMap;
-use crate::problem::Solver;
+use std::fmt::{Display, Formatter};
+use crate::problem::{Answer, split_once, SolveError, Solver};
+use crate::registry::puzzle;
+
+const DAY: u8 = 14;
 
+/// The solver of day 14.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Extended Polymerization", solver: Day {}, input: "input14.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let mut polymerization_device = PolymerizationDevice::new(inp);
-        let elements = polymerization_device.polymerize(10);
+    type Parsed = PolymerizationDevice;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        PolymerizationDevice::new(inp)
+    }
+    fn pt1(&self, polymerization_device: &Self::Parsed) -> Result<Answer, SolveError> {
+        let elements = polymerization_device.polymerize(10)?;
 
         let least = elements[0];
         let most = elements.iter().next_back().unwrap();
 
-        format!("{:?}", most.0 - least.0)
+        Ok((most.0 - least.0).into())
     }
-    fn pt2(&self, inp: &str) -> String {
-        let mut polymerization_device = PolymerizationDevice::new(inp);
-        let elements = polymerization_device.polymerize(40);
+    fn pt2(&self, polymerization_device: &Self::Parsed) -> Result<Answer, SolveError> {
+        let elements = polymerization_device.polymerize(40)?;
 
         let least = elements[0];
         let most = elements.iter().next_back().unwrap();
 
-        format!("{:?}", most.0 - least.0)
+        Ok((most.0 - least.0).into())
     }
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input14.txt").trim()
-}
-
+/// The polymer template and the pair insertion rules.
 #[derive(Debug)]
-struct PolymerizationDevice {
+pub struct PolymerizationDevice {
     template: String,
     rules: HashMap<(char, char), char>,
 }
 
+/// Writes the template and the rules, sorted by pair, in the format of the puzzle input.
+impl Display for PolymerizationDevice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut rules: Vec<(&(char, char), &char)> = self.rules.iter().collect();
+        rules.sort_unstable();
+        writeln!(f, "{}", self.template)?;
+        for ((left, right), insert) in rules {
+            write!(f, "\n{}{} -> {}", left, right, insert)?;
+        }
+        Ok(())
+    }
+}
+
 impl PolymerizationDevice {
-    fn new(inp: &str) -> PolymerizationDevice {
-        let (template, pairs) = inp.split_once("\n\n").unwrap();
+    fn new(inp: &str) -> Result<PolymerizationDevice, SolveError> {
+        let (template, pairs) = split_once(DAY, inp, inp, "\n\n")?;
+        if template.len() < 2 {
+            return Err(SolveError::parse(DAY, inp, template, "expected at least two elements"));
+        }
         let rules_inp = pairs
             .split('\n')
-            .map(|line| line.split_once(" -> ").unwrap())
-            .map(|(polymer, insert)| ((polymer.chars().next().unwrap(), polymer.chars().next_back().unwrap()), insert.chars().next().unwrap()))
-            .collect::<Vec<((char, char), char)>>();
+            .map(|line| {
+                let (polymer, insert) = split_once(DAY, inp, line, " -> ")?;
+                let polymer_chars = polymer.chars().collect::<Vec<char>>();
+                let insert_chars = insert.chars().collect::<Vec<char>>();
+                match (polymer_chars.as_slice(), insert_chars.as_slice()) {
+                    ([left, right], [insert]) => Ok(((*left, *right), *insert)),
+                    ([_, _], _) => Err(SolveError::parse(DAY, inp, insert, "expected a single element")),
+                    _ => Err(SolveError::parse(DAY, inp, polymer, "expected a pair of elements")),
+                }
+            })
+            .collect::<Result<Vec<((char, char), char)>, SolveError>>()?;
         let mut rules = HashMap::new();
         for rule in rules_inp {
             rules.insert(rule.0, rule.1);
         }
-        PolymerizationDevice {
+        Ok(PolymerizationDevice {
             template: template.to_string(),
             rules,
-        }
+        })
     }
 
-    fn polymerize(&mut self, steps: usize) -> Vec<(u64, char)> {
+    /// Applies the pair insertion rules `steps` times to the template, and returns the number of each
+    /// element in the resulting polymer, from least to most common.
+    pub fn polymerize(&self, steps: usize) -> Result<Vec<(u64, char)>, SolveError> {
         let template = self.template.chars().collect::<Vec<char>>();
         let mut single_counts: HashMap<char, u64> = HashMap::new();
         let mut pair_counts: HashMap<(char, char), u64> = HashMap::new();
@@ -65,12 +100,14 @@ impl PolymerizationDevice {
             *pair_counts.entry(pair).or_insert(0) += 1;
         }
 
-        for _ in 0..steps {
+        for step in 1..=steps {
             std::mem::swap(&mut pair_counts, &mut pair_counts_old);
             pair_counts.clear();
 
             for (pair, count) in &pair_counts_old {
-                let &to_insert = self.rules.get(pair).unwrap();
+                let &to_insert = self.rules
+                    .get(pair)
+                    .ok_or_else(|| SolveError::unsolvable(DAY, format!("no insertion rule for '{}{}'", pair.0, pair.1)))?;
                 *single_counts.entry(to_insert).or_insert(0) += count;
 
                 let pair_left = (pair.0, to_insert);
@@ -79,6 +116,13 @@ impl PolymerizationDevice {
                 *pair_counts.entry(pair_left).or_insert(0) += count;
                 *pair_counts.entry(pair_right).or_insert(0) += count;
             }
+            // Every insertion splits a pair in two, creating two pairs.
+            tracing::debug!(
+                step,
+                created = 2 * pair_counts_old.values().sum::<u64>(),
+                distinct = pair_counts.len(),
+                "polymerized"
+            );
         }
 
         let mut elements: Vec<(u64, char)> = vec![];
@@ -86,8 +130,8 @@ impl PolymerizationDevice {
             elements.push((count, element));
 
         }
-        elements.sort_by(|a, b| (a.0.cmp(&b.0)));
-        elements
+        elements.sort_by_key(|a| a.0);
+        Ok(elements)
     }
 }
 
@@ -117,23 +161,47 @@ CC -> N
 CN -> C".trim()
     }
 
+    #[test]
+    fn test_polymerization_device_new_malformed() {
+        assert_eq!(
+            PolymerizationDevice::new("NNCB\n\nCH -> B\nHH => N").unwrap_err().to_string(),
+            "day 14, line 4, column 1: expected ' -> ' ('HH => N')"
+        );
+        assert_eq!(
+            PolymerizationDevice::new("NNCB\n\nCH -> B\nHHH -> N").unwrap_err().to_string(),
+            "day 14, line 4, column 1: expected a pair of elements ('HHH')"
+        );
+        assert_eq!(
+            PolymerizationDevice::new("NNCB\n\nCH -> B\nHH -> NC").unwrap_err().to_string(),
+            "day 14, line 4, column 7: expected a single element ('NC')"
+        );
+    }
+
+    #[test]
+    fn test_polymerize_missing_rule() {
+        assert_eq!(
+            PolymerizationDevice::new("NNC\n\nNN -> C").unwrap().polymerize(1).unwrap_err().to_string(),
+            "day 14: no insertion rule for 'NC'"
+        );
+    }
+
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "1588")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(1588)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "2657")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(2657)))
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "2188189693529")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(2188189693529)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "2911561572630")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(2911561572630)))
     }
 }
@@ -1,29 +1,41 @@
+//! [Day 15: Chiton](https://adventofcode.com/2021/day/15).
+
 use std::fmt::{Display, Formatter};
-use crate::problem::Solver;
+use crate::problem::{Answer, SolveError, Solver};
+use crate::registry::puzzle;
 use pathfinding::prelude::dijkstra;
 
+const DAY: u8 = 15;
+
+/// The solver of day 15.
 pub struct Day {}
 
+puzzle!(year: 2021, day: DAY, title: "Chiton", solver: Day {}, input: "input15.txt");
+
 impl Solver for Day {
-    fn pt1(&self, inp: &str) -> String {
-        let map = Map::new(inp);
-        let (_path, risk) = map.navigate(0, map.risks.len() - 1);
-        format!("{}", risk)
+    type Parsed = Map;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+        Map::new(inp)
+    }
+    fn pt1(&self, map: &Self::Parsed) -> Result<Answer, SolveError> {
+        let (_path, risk) = map.navigate(0, map.risks.len() - 1)?;
+        Ok(risk.into())
     }
-    fn pt2(&self, inp: &str) -> String {
-        let mut map = Map::new(inp);
+    fn pt2(&self, map: &Self::Parsed) -> Result<Answer, SolveError> {
+        let mut map = map.clone();
         map.grow(5);
-        let (_path, risk) = map.navigate(0, map.risks.len() - 1);
-        format!("{}", risk)
+        let (_path, risk) = map.navigate(0, map.risks.len() - 1)?;
+        Ok(risk.into())
+    }
+    fn visualize(&self, map: &Self::Parsed) -> Option<String> {
+        Some(map.to_string())
     }
 }
 
-pub(crate) fn input() -> &'static str {
-    include_str!("input15.txt").trim()
-}
-
-#[derive(Debug, Eq, PartialEq)]
-struct Map {
+/// The risk level map of the cavern.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Map {
     risks: Vec<u32>,
     width: usize,
 }
@@ -31,8 +43,8 @@ struct Map {
 impl Display for Map {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let height = self.risks.len() / self.width;
-        let str = (0..height).into_iter().map(|y| {
-            (0..self.width).into_iter().map(|x| {
+        let str = (0..height).map(|y| {
+            (0..self.width).map(|x| {
                 let index = self.index(x, y, &self.risks, self.width).unwrap();
                 self.risks[index].to_string()
             }).collect::<String>()
@@ -42,21 +54,33 @@ impl Display for Map {
 }
 
 impl Map {
-    fn new(inp: &str) -> Map {
+    fn new(inp: &str) -> Result<Map, SolveError> {
         let lines = inp.split('\n').collect::<Vec<&str>>();
         let width = lines.first().unwrap().len();
-        let risks = lines
-            .iter()
-            .map(|line| line.chars().map(|c| c.to_string().parse::<u32>().unwrap()).collect::<Vec<u32>>())
-            .collect::<Vec<Vec<u32>>>()
-            .concat();
-        Map {
+        if width == 0 {
+            return Err(SolveError::parse(DAY, inp, lines[0], "expected a row of digits"));
+        }
+        let mut risks: Vec<u32> = vec![];
+        for line in &lines {
+            if line.len() != width {
+                return Err(SolveError::parse(DAY, inp, line, format!("expected {} digits", width)));
+            }
+            for (i, c) in line.char_indices() {
+                let risk = c
+                    .to_digit(10)
+                    .ok_or_else(|| SolveError::parse(DAY, inp, &line[i..i + c.len_utf8()], "expected a digit"))?;
+                risks.push(risk);
+            }
+        }
+        Ok(Map {
             risks,
             width,
-        }
+        })
     }
 
-    fn grow(&mut self, times: usize) {
+    /// Tiles the map `times` times in both directions, increasing the risk of each tile by its distance
+    /// from the original, wrapping around from 9 to 1.
+    pub fn grow(&mut self, times: usize) {
         let height = self.risks.len() / self.width;
         let height_new = height * times;
         let width_new = self.width * times;
@@ -77,15 +101,20 @@ impl Map {
                 risks[target_index] = target_risk;
             }
         }
+        tracing::debug!(width = width_new, height = height_new, "grown");
         self.width = width_new;
         self.risks = risks;
     }
 
-    fn navigate(&self, from: usize, to: usize) -> (Vec<usize>, u32) {
-        dijkstra(&from,
+    /// Finds the path of lowest total risk between two positions, given as indexes in row-major order.
+    /// Returns the positions along the path, including both ends, and its total risk excluding `from`.
+    pub fn navigate(&self, from: usize, to: usize) -> Result<(Vec<usize>, u32), SolveError> {
+        let (path, risk) = dijkstra(&from,
                  |&index| self.adjacent(index).into_iter().map(|a| (a, self.risks[a])),
                  |&p| p == to,
-        ).unwrap()
+        ).ok_or_else(|| SolveError::unsolvable(DAY, format!("there is no path from {} to {}", from, to)))?;
+        tracing::debug!(from = ?self.xy(from, self.width), to = ?self.xy(to, self.width), steps = path.len() - 1, risk, "navigated");
+        Ok((path, risk))
     }
 
     fn xy(&self, index: usize, width: usize) -> (usize, usize) {
@@ -106,10 +135,9 @@ impl Map {
 
     fn adjacent(&self, index: usize) -> Vec<usize> {
         let (x, y) = self.xy(index, self.width);
-        vec![(x, y + 1), (x + 1, y), (x, y - 1), (x - 1, y)]
+        vec![(x, y + 1), (x + 1, y), (x, y.wrapping_sub(1)), (x.wrapping_sub(1), y)]
             .into_iter()
-            .map(|(px, py)| self.index(px, py, &self.risks, self.width))
-            .flatten()
+            .filter_map(|(px, py)| self.index(px, py, &self.risks, self.width))
             .collect()
     }
 }
@@ -212,7 +240,7 @@ mod tests {
 
     #[test]
     fn test_xy_example() {
-        let map = Map::new(example_input());
+        let map = Map::new(example_input()).unwrap();
         assert_eq!((0, 0), map.xy(0, map.width));
         assert_eq!((0, 1), map.xy(10, map.width));
         assert_eq!((9, 9), map.xy(99, map.width));
@@ -220,7 +248,7 @@ mod tests {
 
     #[test]
     fn test_adjacent_example() {
-        let map = Map::new(example_input());
+        let map = Map::new(example_input()).unwrap();
         assert_eq!(vec![10, 1], map.adjacent(0));
         assert_eq!(vec![19, 8], map.adjacent(9));
         assert_eq!(vec![20, 11, 0], map.adjacent(10));
@@ -229,46 +257,60 @@ mod tests {
 
     #[test]
     fn test_navigate_example() {
-        let map = Map::new(example_input());
+        let map = Map::new(example_input()).unwrap();
         assert_eq!(
             (vec![0, 10, 20, 21, 22, 23, 24, 25, 26, 36, 37, 47, 57, 58, 68, 78, 88, 89, 99], 40),
-            map.navigate(0, 99)
+            map.navigate(0, 99).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_map_new_malformed() {
+        assert_eq!(
+            Map::new("1163\n13a1").unwrap_err().to_string(),
+            "day 15, line 2, column 3: expected a digit ('a')"
+        );
+        assert_eq!(
+            Map::new("1163\n138").unwrap_err().to_string(),
+            "day 15, line 2, column 1: expected 4 digits ('138')"
         );
+        assert_eq!(Map::new("").unwrap_err().to_string(), "day 15, line 1, column 1: expected a row of digits ('')");
+        assert_eq!(Day {}.solve_pt2(""), Err(Map::new("").unwrap_err()));
     }
 
     #[test]
     fn test_pt1_example() {
-        assert_eq!(Day {}.pt1(example_input()), "40")
+        assert_eq!(Day {}.solve_pt1(example_input()), Ok(Answer::Integer(40)))
     }
 
     #[test]
     fn test_pt1() {
-        assert_eq!(Day {}.pt1(input()), "403")
+        assert_eq!(Day {}.solve_pt1(input()), Ok(Answer::Integer(403)))
     }
 
     #[test]
     fn test_grow_2() {
-        let expected = Map::new(example_input_grown_2());
-        let mut map = Map::new(example_input());
+        let expected = Map::new(example_input_grown_2()).unwrap();
+        let mut map = Map::new(example_input()).unwrap();
         map.grow(2);
         assert_eq!(map.to_string(), expected.to_string())
     }
 
     #[test]
     fn test_grow_5() {
-        let expected = Map::new(example_input_grown_5());
-        let mut map = Map::new(example_input());
+        let expected = Map::new(example_input_grown_5()).unwrap();
+        let mut map = Map::new(example_input()).unwrap();
         map.grow(5);
         assert_eq!(map.to_string(), expected.to_string())
     }
 
     #[test]
     fn test_pt2_example() {
-        assert_eq!(Day {}.pt2(example_input()), "315")
+        assert_eq!(Day {}.solve_pt2(example_input()), Ok(Answer::Integer(315)))
     }
 
     #[test]
     fn test_pt2() {
-        assert_eq!(Day {}.pt2(input()), "2840")
+        assert_eq!(Day {}.solve_pt2(input()), Ok(Answer::Integer(2840)))
     }
 }
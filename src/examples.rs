@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+
+/// Directory the example inputs are read from.
+pub(crate) const DEFAULT_EXAMPLES_DIR: &str = "tests/fixtures";
+
+/// Returns the directory of the examples of a day, `<dir>/YYYY/dayNN`.
+pub(crate) fn day_dir(dir: &Path, year: u16, day: u8) -> PathBuf {
+    dir.join(year.to_string()).join(format!("day{:02}", day))
+}
+
+/// Returns the known answers of an example, kept next to it in a `.toml` file with bare `part1` and `part2` keys.
+pub(crate) fn answers_path(example: &Path) -> PathBuf {
+    example.with_extension("toml")
+}
+
+/// Returns the `.txt` files in `dir`, sorted by name. A missing or unreadable directory has no examples.
+pub(crate) fn files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && path.extension().map(|extension| extension == "txt").unwrap_or(false))
+            .collect(),
+        Err(_) => vec![],
+    };
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::answers::Answers;
+    use crate::registry;
+
+    use super::*;
+
+    // One test per example, calling `check`, generated by `build.rs`.
+    include!(concat!(env!("OUT_DIR"), "/example_tests.rs"));
+
+    /// Solves an example with every variant of its day, expecting the answers of its `.toml` file.
+    fn check(year: u16, day: u8, name: &str) {
+        let dir = day_dir(&Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_EXAMPLES_DIR), year, day);
+        let path = dir.join(name).with_extension("txt");
+        let inp = std::fs::read_to_string(&path).unwrap();
+        let answers = Answers::load_day(&answers_path(&path).to_string_lossy(), day).unwrap();
+        let parts: Vec<u8> = [1, 2].into_iter().filter(|part| answers.get(day, *part).is_some()).collect();
+        assert!(!parts.is_empty(), "no answers for day {} next to '{}'", day, path.display());
+
+        let puzzle = registry::find(year, day).unwrap_or_else(|| panic!("no solver for {} day {}", year, day));
+        for variant in puzzle.variant_names() {
+            let parsed = puzzle.variant(variant).unwrap().parse(inp.trim()).unwrap();
+            for &part in &parts {
+                let answer = if part == 1 { parsed.pt1() } else { parsed.pt2() };
+                let expected = answers.get(day, part).unwrap();
+                assert_eq!(answer.as_ref(), Ok(expected), "part {} of {} with the {} variant", part, path.display(), variant);
+            }
+        }
+    }
+
+    #[test]
+    fn test_answers_path() {
+        assert_eq!(answers_path(Path::new("tests/fixtures/2021/day12/2.txt")), Path::new("tests/fixtures/2021/day12/2.toml"));
+    }
+
+    #[test]
+    fn test_files() {
+        let dir = std::env::temp_dir().join(format!("adventofcode-2021-examples-{}", std::process::id()));
+        let day = day_dir(&dir, 2021, 7);
+        assert_eq!(day, dir.join("2021").join("day07"));
+        assert!(files(&day).is_empty());
+
+        std::fs::create_dir_all(&day).unwrap();
+        for name in ["2.txt", "1.txt", "notes.md"] {
+            std::fs::write(day.join(name), "16,1,2,0,4,2,7,1,2,14").unwrap();
+        }
+        let found = files(&day);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(found, vec![day.join("1.txt"), day.join("2.txt")]);
+    }
+}
@@ -0,0 +1,218 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Writes the events of the explained days as lines prefixed with the spans they happened in, such as
+/// `year 2021 day 4 part 1: board won board=3 draw=12 number=24 score=4512`.
+///
+/// Release builds with the default `quiet-release` feature leave the events of the solvers out entirely, with tracing's
+/// `release_max_level_off`. In other builds solvers emit their events whether or not they are explained, and without a
+/// subscriber those events are disabled by a single check, so the subscriber is only installed when explaining.
+pub(crate) struct Explainer {
+    days: Vec<u8>,
+    write: Box<dyn Fn(&str) + Send + Sync>,
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, Span>>,
+}
+
+struct Span {
+    name: &'static str,
+    fields: Fields,
+    /// Whether this span is, or is inside, the span of an explained day.
+    explained: bool,
+    references: usize,
+}
+
+impl Span {
+    /// The fields as `name value`, or the name of a span without fields.
+    fn label(&self) -> String {
+        if self.fields.values.is_empty() {
+            return self.name.to_string();
+        }
+        self.fields.values.iter().map(|(name, value)| format!("{} {}", name, value)).collect::<Vec<String>>().join(" ")
+    }
+}
+
+/// The message and other fields of an event or span.
+#[derive(Default)]
+struct Fields {
+    message: Option<String>,
+    values: Vec<(&'static str, String)>,
+}
+
+impl Fields {
+    fn set(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = Some(value);
+        } else if let Some(entry) = self.values.iter_mut().find(|(name, _)| *name == field.name()) {
+            entry.1 = value;
+        } else {
+            self.values.push((field.name(), value));
+        }
+    }
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.set(field, value.to_string());
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.set(field, format!("{:?}", value));
+    }
+}
+
+thread_local! {
+    /// The spans entered on this thread, innermost last.
+    static ENTERED: RefCell<Vec<u64>> = const { RefCell::new(vec![]) };
+}
+
+impl Explainer {
+    /// Explains `days`, passing every line to `write`.
+    pub(crate) fn new(days: Vec<u8>, write: impl Fn(&str) + Send + Sync + 'static) -> Explainer {
+        Explainer { days, write: Box::new(write), next_id: AtomicU64::new(1), spans: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl Subscriber for Explainer {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut fields = Fields::default();
+        attributes.record(&mut fields);
+        let mut spans = self.spans.lock().unwrap();
+        let day = fields.values.iter().find(|(name, _)| *name == "day").and_then(|(_, day)| day.parse::<u8>().ok());
+        let explained = match day {
+            Some(day) => self.days.contains(&day),
+            None => ENTERED.with(|entered| entered.borrow().last().and_then(|id| spans.get(id)).map(|span| span.explained))
+                .unwrap_or(false),
+        };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        spans.insert(id, Span { name: attributes.metadata().name(), fields, explained, references: 1 });
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(span) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            values.record(&mut span.fields);
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let labels = {
+            let spans = self.spans.lock().unwrap();
+            let entered = ENTERED.with(|entered| entered.borrow().clone());
+            if !entered.last().and_then(|id| spans.get(id)).map(|span| span.explained).unwrap_or(false) {
+                return;
+            }
+            entered.iter().filter_map(|id| spans.get(id)).map(Span::label).collect::<Vec<String>>()
+        };
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let mut words: Vec<String> = fields.message.into_iter().collect();
+        words.extend(fields.values.iter().map(|(name, value)| format!("{}={}", name, value)));
+        (self.write)(&format!("{}: {}", labels.join(" "), words.join(" ")));
+    }
+
+    fn enter(&self, span: &Id) {
+        ENTERED.with(|entered| entered.borrow_mut().push(span.into_u64()));
+    }
+
+    fn exit(&self, span: &Id) {
+        ENTERED.with(|entered| {
+            let mut entered = entered.borrow_mut();
+            if let Some(position) = entered.iter().rposition(|id| *id == span.into_u64()) {
+                entered.remove(position);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(span) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            span.references += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = self.spans.lock().unwrap();
+        let closed = match spans.get_mut(&span.into_u64()) {
+            Some(data) => {
+                data.references -= 1;
+                data.references == 0
+            }
+            None => false,
+        };
+        if closed {
+            spans.remove(&span.into_u64());
+        }
+        closed
+    }
+}
+
+/// Writes the events of the days in `explain`, or of all `selected` days if it is empty, to stderr from now on.
+/// Returns false if this build has no events to write.
+pub(crate) fn install(explain: &[u8], selected: &[u8]) -> bool {
+    if tracing::level_filters::STATIC_MAX_LEVEL < tracing::level_filters::LevelFilter::DEBUG {
+        eprintln!("Release builds with the quiet-release feature leave out the events of the solvers, explain with a debug build or without that feature instead");
+        return false;
+    }
+    let days = if explain.is_empty() { selected } else { explain };
+    tracing::subscriber::set_global_default(Explainer::new(days.to_vec(), |line| eprintln!("{}", line)))
+        .expect("no other subscriber is installed");
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn explain(days: Vec<u8>, f: impl FnOnce()) -> Vec<String> {
+        let lines = Arc::new(Mutex::new(vec![]));
+        let written = lines.clone();
+        let explainer = Explainer::new(days, move |line| written.lock().unwrap().push(line.to_string()));
+        tracing::subscriber::with_default(explainer, f);
+        let lines = lines.lock().unwrap().clone();
+        lines
+    }
+
+    #[test]
+    #[cfg_attr(all(not(debug_assertions), feature = "quiet-release"), ignore = "quiet release builds leave out the events")]
+    fn test_explainer() {
+        let lines = explain(vec![4], || {
+            tracing::debug!("outside of a day");
+            for day in [3, 4] {
+                let _day = tracing::debug_span!("day", year = 2021, day).entered();
+                tracing::debug_span!("parse").in_scope(|| tracing::debug!(boards = 3, "parsed"));
+                let _part = tracing::debug_span!("part", part = 1).entered();
+                tracing::debug!(board = 2, draw = 12, "board won");
+            }
+        });
+        assert_eq!(lines, vec![
+            "year 2021 day 4 parse: parsed boards=3",
+            "year 2021 day 4 part 1: board won board=2 draw=12",
+        ]);
+    }
+
+    #[test]
+    #[cfg_attr(all(not(debug_assertions), feature = "quiet-release"), ignore = "quiet release builds leave out the events")]
+    fn test_explainer_record() {
+        let lines = explain(vec![13], || {
+            let day = tracing::debug_span!("day", day = 13, fold = tracing::field::Empty).entered();
+            day.record("fold", "x=5");
+            tracing::debug!(dots = 16, "folded");
+        });
+        assert_eq!(lines, vec!["day 13 fold x=5: folded dots=16"]);
+    }
+}
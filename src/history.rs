@@ -0,0 +1,445 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::bench::Stats;
+use crate::cli::History;
+use crate::registry;
+use crate::runner::DayResult;
+
+/// The history file, unless another one is given with `--history`. Like the inputs and the submissions, it is kept in
+/// the working directory.
+pub(crate) const DEFAULT_PATH: &str = "history.tsv";
+
+const HEADER: &str = "timestamp\tcommit\tprofile\tfeatures\tjobs\tmachine\tyear\tday\tvariant\tstep\tduration_ns";
+/// The header of histories recorded before the features and jobs were, whose runs are read with unknown features.
+const HEADER_WITHOUT_FEATURES: &str = "timestamp\tcommit\tprofile\tmachine\tyear\tday\tvariant\tstep\tduration_ns";
+
+/// What a run was made with. Timings are only compared between runs of the same profile, features and number of
+/// jobs on the same machine.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Build {
+    /// The abbreviated hash of the commit the binary was built from, with `-dirty` if there were uncommitted changes.
+    pub(crate) commit: String,
+    /// `debug` or `release`.
+    pub(crate) profile: String,
+    /// The enabled cargo features that change timings, separated by commas, or `-` for none.
+    pub(crate) features: String,
+    /// The number of threads the days were run on.
+    pub(crate) jobs: usize,
+    pub(crate) machine: String,
+}
+
+impl Build {
+    /// Describes the running binary, run with `jobs` threads on this machine.
+    pub(crate) fn current(jobs: usize) -> Build {
+        Build {
+            commit: env!("BUILD_COMMIT").to_string(),
+            profile: profile().to_string(),
+            features: features(),
+            jobs,
+            machine: machine(),
+        }
+    }
+
+    /// Whether the timings of runs of this and the `other` build can be compared.
+    pub(crate) fn comparable(&self, other: &Build) -> bool {
+        self.profile == other.profile && self.features == other.features && self.jobs == other.jobs && self.machine == other.machine
+    }
+}
+
+fn profile() -> &'static str {
+    if cfg!(debug_assertions) { "debug" } else { "release" }
+}
+
+fn features() -> String {
+    let features: Vec<&str> = [
+        ("embedded-inputs", cfg!(feature = "embedded-inputs")),
+        ("quiet-release", cfg!(feature = "quiet-release")),
+        ("count-allocations", cfg!(feature = "count-allocations")),
+    ]
+    .iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(feature, _)| *feature)
+    .collect();
+    if features.is_empty() { "-".to_string() } else { features.join(",") }
+}
+
+fn machine() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .chain(std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty() && !name.contains('\t'))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Seconds since the Unix epoch.
+pub(crate) fn timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0)
+}
+
+/// The time one step took in one run.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Entry {
+    pub(crate) timestamp: u64,
+    pub(crate) build: Build,
+    pub(crate) year: u16,
+    pub(crate) day: u8,
+    pub(crate) variant: String,
+    /// `parse`, `pt1` or `pt2`.
+    pub(crate) step: String,
+    pub(crate) duration: Duration,
+}
+
+/// Returns the entries of the steps of `results` that succeeded, as run by `variant`.
+pub(crate) fn entries(timestamp: u64, build: &Build, variant: &str, results: &[DayResult]) -> Vec<Entry> {
+    let entry = |result: &DayResult, step: String, duration: Duration| Entry {
+        timestamp,
+        build: build.clone(),
+        year: result.year,
+        day: result.day,
+        variant: variant.to_string(),
+        step,
+        duration,
+    };
+    let mut entries = vec![];
+    for result in results.iter().filter(|result| result.failure.is_none()) {
+        entries.push(entry(result, "parse".to_string(), result.parse));
+        for part in result.parts.iter().filter(|part| part.answer.is_ok()) {
+            entries.push(entry(result, format!("pt{}", part.part), part.elapsed));
+        }
+    }
+    entries
+}
+
+fn format_entry(entry: &Entry) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        entry.timestamp,
+        entry.build.commit,
+        entry.build.profile,
+        entry.build.features,
+        entry.build.jobs,
+        entry.build.machine,
+        entry.year,
+        entry.day,
+        entry.variant,
+        entry.step,
+        entry.duration.as_nanos(),
+    )
+}
+
+/// Appends `entries` to the history at `path`, creating it with a header if it doesn't exist.
+pub(crate) fn append(path: &Path, entries: &[Entry]) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let failed = |e: std::io::Error| format!("Failed to write '{}': {}", path.display(), e);
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path).map_err(failed)?;
+    let mut out = String::new();
+    if file.metadata().map_err(failed)?.len() == 0 {
+        out.push_str(HEADER);
+        out.push('\n');
+    }
+    for entry in entries {
+        out.push_str(&format_entry(entry));
+        out.push('\n');
+    }
+    file.write_all(out.as_bytes()).map_err(failed)
+}
+
+/// Reads the history at `path`, which is empty if there is no such file yet.
+pub(crate) fn load(path: &Path) -> Result<Vec<Entry>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(inp) => parse(&inp).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(format!("Failed to read '{}': {}", path.display(), e)),
+    }
+}
+
+fn parse(inp: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+    for (number, line) in inp.lines().enumerate() {
+        if line.trim().is_empty() || line == HEADER || line == HEADER_WITHOUT_FEATURES {
+            continue;
+        }
+        let malformed = || format!("line {}: malformed history entry '{}'", number + 1, line);
+        let mut fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() == 9 {
+            // Runs without features and jobs are kept, but aren't comparable to any build.
+            fields.splice(3..3, ["unknown", "0"]);
+        }
+        if fields.len() != 11 {
+            return Err(malformed());
+        }
+        entries.push(Entry {
+            timestamp: fields[0].parse().map_err(|_| malformed())?,
+            build: Build {
+                commit: fields[1].to_string(),
+                profile: fields[2].to_string(),
+                features: fields[3].to_string(),
+                jobs: fields[4].parse().map_err(|_| malformed())?,
+                machine: fields[5].to_string(),
+            },
+            year: fields[6].parse().map_err(|_| malformed())?,
+            day: fields[7].parse().map_err(|_| malformed())?,
+            variant: fields[8].to_string(),
+            step: fields[9].to_string(),
+            duration: Duration::from_nanos(fields[10].parse().map_err(|_| malformed())?),
+        });
+    }
+    Ok(entries)
+}
+
+/// The recent timings of one step of one day.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Trend {
+    pub(crate) day: u8,
+    pub(crate) variant: String,
+    pub(crate) step: String,
+    /// The last recorded runs, oldest first, ending with the latest run.
+    pub(crate) recent: Vec<Duration>,
+    /// The commit of the latest run.
+    pub(crate) commit: String,
+    /// The number of recorded runs.
+    pub(crate) runs: usize,
+}
+
+impl Trend {
+    pub(crate) fn latest(&self) -> Duration {
+        *self.recent.last().expect("at least one run")
+    }
+
+    /// The median of the runs before the latest one, if there are any.
+    pub(crate) fn median(&self) -> Option<Duration> {
+        let previous = &self.recent[..self.recent.len() - 1];
+        if previous.is_empty() { None } else { Some(Stats::new(previous).median) }
+    }
+
+    /// How many times slower the latest run was than the median of the runs before it.
+    pub(crate) fn ratio(&self) -> Option<f64> {
+        self.median().filter(|median| !median.is_zero()).map(|median| self.latest().as_secs_f64() / median.as_secs_f64())
+    }
+}
+
+/// Returns the trends of `days` of `year` in the runs made with a build comparable to `build`, in day, variant and
+/// step order. Each trend holds the latest run and up to `window` runs before it.
+pub(crate) fn trends(entries: &[Entry], build: &Build, year: u16, days: &[u8], window: usize) -> Vec<Trend> {
+    let mut series: BTreeMap<(u8, &str, &str), Vec<&Entry>> = BTreeMap::new();
+    for entry in entries {
+        if entry.build.comparable(build) && entry.year == year && days.contains(&entry.day) {
+            series.entry((entry.day, &entry.variant, &entry.step)).or_default().push(entry);
+        }
+    }
+    series
+        .into_iter()
+        .map(|((day, variant, step), entries)| {
+            let recent = &entries[entries.len().saturating_sub(window + 1)..];
+            Trend {
+                day,
+                variant: variant.to_string(),
+                step: step.to_string(),
+                recent: recent.iter().map(|entry| entry.duration).collect(),
+                commit: recent.last().unwrap().build.commit.clone(),
+                runs: entries.len(),
+            }
+        })
+        .collect()
+}
+
+/// Draws `durations` as a line of bars, from the shortest to the longest.
+pub(crate) fn sparkline(durations: &[Duration]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let min = durations.iter().min().copied().unwrap_or_default();
+    let max = durations.iter().max().copied().unwrap_or_default();
+    let range = (max - min).as_secs_f64();
+    durations
+        .iter()
+        .map(|duration| {
+            let level = if range == 0.0 { 0.0 } else { (*duration - min).as_secs_f64() / range };
+            BARS[(level * (BARS.len() - 1) as f64).round() as usize]
+        })
+        .collect()
+}
+
+/// Prints the trends of `days` of `year`. Returns false if the history could not be read, or if the latest run of
+/// any step was slower than `history.factor` times its rolling median.
+pub(crate) fn run(history: &History, year: u16, days: &[u8]) -> bool {
+    let path = Path::new(history.path.as_deref().unwrap_or(DEFAULT_PATH));
+    let entries = match load(path) {
+        Ok(entries) => entries,
+        Err(message) => {
+            eprintln!("{}", message);
+            return false;
+        }
+    };
+    let mut build = Build::current(history.jobs);
+    if let Some(profile) = &history.profile {
+        build.profile = profile.clone();
+    }
+    let runs = format!(
+        "{} runs of {} with features {} on {} {} on {}",
+        build.profile,
+        year,
+        build.features,
+        build.jobs,
+        if build.jobs == 1 { "thread" } else { "threads" },
+        build.machine
+    );
+    let trends = trends(&entries, &build, year, days, history.window);
+    if trends.is_empty() {
+        println!("No {} in '{}'", runs, path.display());
+        return true;
+    }
+
+    let labels: Vec<String> = trends
+        .iter()
+        .map(|trend| match trend.variant.as_str() {
+            registry::DEFAULT_VARIANT => format!("day{:02} - {}", trend.day, trend.step),
+            variant => format!("day{:02} - {} ({})", trend.day, trend.step, variant),
+        })
+        .collect();
+    let width = labels.iter().map(|label| label.len()).max().unwrap_or(0);
+    println!("{}, against the median of up to {} runs before the latest", runs, history.window);
+    println!("{:width$}  {:>10} {:>10} {:>7}  {:<w$}  commit", "", "latest", "median", "change", "trend", w = history.window + 1);
+    let mut slower = 0;
+    for (label, trend) in labels.iter().zip(&trends) {
+        let median = trend.median().map(|median| format!("{:.2?}", median)).unwrap_or_else(|| "-".to_string());
+        let change = trend.ratio().map(|ratio| format!("{:.2}x", ratio)).unwrap_or_else(|| "-".to_string());
+        let mut line = format!(
+            "{:width$}: {:>10.2?} {:>10} {:>7}  {:<w$}  {} ({} runs)",
+            label,
+            trend.latest(),
+            median,
+            change,
+            sparkline(&trend.recent),
+            trend.commit,
+            trend.runs,
+            w = history.window + 1
+        );
+        if trend.ratio().map(|ratio| ratio > history.factor).unwrap_or(false) {
+            line.push_str(" SLOWER");
+            slower += 1;
+        }
+        println!("{}", line);
+    }
+    println!("history: {} of {} steps more than {}x slower than their median", slower, trends.len(), history.factor);
+    slower == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::{Answer, SolveError};
+    use crate::runner::{Failure, PartResult, StepError};
+
+    fn build(commit: &str, profile: &str) -> Build {
+        Build { commit: commit.to_string(), profile: profile.to_string(), features: "-".to_string(), jobs: 1, machine: "box".to_string() }
+    }
+
+    fn entry(day: u8, step: &str, micros: u64, profile: &str) -> Entry {
+        Entry {
+            timestamp: 1_640_000_000,
+            build: build("abc1234", profile),
+            year: 2021,
+            day,
+            variant: registry::DEFAULT_VARIANT.to_string(),
+            step: step.to_string(),
+            duration: Duration::from_micros(micros),
+        }
+    }
+
+    #[test]
+    fn test_entries() {
+        let results = vec![
+            DayResult {
+                year: 2021,
+                day: 7,
+                parse: Duration::from_micros(40),
+                parse_allocations: None,
+                failure: None,
+                parts: vec![
+                    PartResult { part: 1, elapsed: Duration::from_micros(900), allocations: None, answer: Ok(Answer::Integer(37)) },
+                    PartResult {
+                        part: 2,
+                        elapsed: Duration::from_micros(5),
+                        allocations: None,
+                        answer: Err(StepError::Solve(SolveError::unsolvable(7, "no crabs"))),
+                    },
+                ],
+            },
+            DayResult {
+                year: 2021,
+                day: 8,
+                parse: Duration::ZERO,
+                parse_allocations: None,
+                failure: Some(Failure::Input("No input for day 08".to_string())),
+                parts: vec![],
+            },
+        ];
+        let entries = entries(1_640_000_000, &build("abc1234", "release"), "default", &results);
+        assert_eq!(entries, vec![entry(7, "parse", 40, "release"), entry(7, "pt1", 900, "release")]);
+    }
+
+    #[test]
+    fn test_parse_roundtrip() {
+        let entries = vec![entry(7, "parse", 40, "release"), entry(7, "pt1", 900, "debug")];
+        let inp = format!("{}\n{}\n{}\n", HEADER, format_entry(&entries[0]), format_entry(&entries[1]));
+        assert_eq!(parse(&inp), Ok(entries));
+        let build = Build { features: "unknown".to_string(), jobs: 0, ..build("abc1234", "release") };
+        assert_eq!(
+            parse(&format!("{}\n1640000000\tabc1234\trelease\tbox\t2021\t7\tdefault\tpt1\t900000\n", HEADER_WITHOUT_FEATURES)),
+            Ok(vec![Entry { build, ..entry(7, "pt1", 900, "release") }])
+        );
+        assert!(parse("1640000000\tabc1234\trelease\t-\t1\tbox\t2021\t7\tdefault\tpt1").is_err());
+        assert!(parse("soon\tabc1234\trelease\t-\t1\tbox\t2021\t7\tdefault\tpt1\t900").is_err());
+        assert!(parse("1640000000\tabc1234\trelease\t-\tmany\tbox\t2021\t7\tdefault\tpt1\t900").is_err());
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let path = std::env::temp_dir().join(format!("adventofcode-2021-history-{}.tsv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(load(&path), Ok(vec![]));
+        append(&path, &[entry(1, "pt1", 10, "release")]).unwrap();
+        append(&path, &[entry(1, "pt1", 12, "release")]).unwrap();
+        let inp = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(inp.matches(HEADER).count(), 1);
+        assert_eq!(parse(&inp), Ok(vec![entry(1, "pt1", 10, "release"), entry(1, "pt1", 12, "release")]));
+    }
+
+    #[test]
+    fn test_trends() {
+        let mut entries: Vec<Entry> = [100, 110, 90, 400, 105, 250].iter().map(|micros| entry(7, "pt1", *micros, "release")).collect();
+        entries.push(entry(7, "pt1", 5000, "debug"));
+        entries.push(Entry { build: Build { jobs: 4, ..build("abc1234", "release") }, ..entry(7, "pt1", 3000, "release") });
+        entries.push(Entry { build: Build { features: "count-allocations".to_string(), ..build("abc1234", "release") }, ..entry(7, "pt1", 3000, "release") });
+        entries.push(entry(7, "parse", 30, "release"));
+        entries.push(entry(8, "pt1", 30, "release"));
+
+        let trends = trends(&entries, &build("def5678", "release"), 2021, &[7], 3);
+        assert_eq!(trends.len(), 2);
+        assert_eq!(trends[0].step, "parse");
+        assert_eq!(trends[0].median(), None);
+        assert_eq!(trends[0].ratio(), None);
+
+        let pt1 = &trends[1];
+        assert_eq!(pt1.runs, 6);
+        assert_eq!(pt1.recent, [90, 400, 105, 250].iter().map(|micros| Duration::from_micros(*micros)).collect::<Vec<_>>());
+        assert_eq!(pt1.latest(), Duration::from_micros(250));
+        assert_eq!(pt1.median(), Some(Duration::from_micros(105)));
+        assert!((pt1.ratio().unwrap() - 250.0 / 105.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        let durations: Vec<Duration> = [10, 80, 45, 10].iter().map(|micros| Duration::from_micros(*micros)).collect();
+        assert_eq!(sparkline(&durations), "▁█▅▁");
+        assert_eq!(sparkline(&[Duration::from_micros(3); 3]), "▁▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
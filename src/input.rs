@@ -0,0 +1,123 @@
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Environment variable overriding the directory runtime inputs are read from.
+pub(crate) const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+/// Directory runtime inputs are read from when `AOC_INPUT_DIR` is not set.
+pub(crate) const DEFAULT_INPUT_DIR: &str = "inputs";
+
+/// Finds the puzzle input for a day.
+///
+/// Inputs are looked up in order from:
+/// 1. an explicit path (`--input <path>`, where `-` means stdin),
+/// 2. `<dir>/YYYY/dayNN.txt`, where `dir` is `$AOC_INPUT_DIR` or `inputs/`,
+/// 3. the input embedded in the binary, if built with the `embedded-inputs` feature.
+#[derive(Debug)]
+pub(crate) struct Resolver {
+    pub(crate) path: Option<String>,
+    pub(crate) dir: PathBuf,
+    pub(crate) embedded: bool,
+}
+
+impl Resolver {
+    pub(crate) fn from_env(path: Option<String>) -> Resolver {
+        let dir = std::env::var_os(INPUT_DIR_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_INPUT_DIR));
+        Resolver { path, dir, embedded: cfg!(feature = "embedded-inputs") }
+    }
+
+    pub(crate) fn day_path(&self, year: u16, day: u8) -> PathBuf {
+        self.dir.join(year.to_string()).join(format!("day{:02}.txt", day))
+    }
+
+    pub(crate) fn resolve(&self, year: u16, day: u8) -> Result<String, String> {
+        if let Some(path) = &self.path {
+            return if path == "-" {
+                let mut inp = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut inp)
+                    .map_err(|e| format!("Failed to read input for {} day {:02} from stdin: {}", year, day, e))?;
+                Ok(inp.trim().to_string())
+            } else {
+                std::fs::read_to_string(path)
+                    .map(|inp| inp.trim().to_string())
+                    .map_err(|e| format!("Failed to read input for {} day {:02} from '{}': {}", year, day, path, e))
+            };
+        }
+
+        let day_path = self.day_path(year, day);
+        match std::fs::read_to_string(&day_path) {
+            Ok(inp) => return Ok(inp.trim().to_string()),
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Failed to read input for {} day {:02} from '{}': {}", year, day, day_path.display(), e));
+            }
+            Err(_) => {}
+        }
+
+        if self.embedded {
+            // A new day embeds its empty input file until the input is pasted into it.
+            let input = crate::registry::find(year, day).and_then(|puzzle| puzzle.input).map(|input| input());
+            if let Some(input) = input.filter(|input| !input.is_empty()) {
+                return Ok(input.to_string());
+            }
+        }
+
+        Err(format!(
+            "No input found for {} day {:02}: pass --input <path>, create '{}' (or set ${}){}",
+            year,
+            day,
+            day_path.display(),
+            INPUT_DIR_VAR,
+            if cfg!(feature = "embedded-inputs") { "" } else { ", or build with --features embedded-inputs" },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("adventofcode-2021-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_resolve_explicit_path() {
+        let dir = temp_dir("explicit");
+        let path = dir.join("mine.txt");
+        std::fs::write(&path, "1\n2\n3\n").unwrap();
+        let resolver = Resolver { path: Some(path.display().to_string()), dir: dir.join("missing"), embedded: false };
+        assert_eq!(resolver.resolve(2021, 1), Ok("1\n2\n3".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_input_dir() {
+        let dir = temp_dir("dir");
+        std::fs::create_dir_all(dir.join("2021")).unwrap();
+        std::fs::write(dir.join("2021").join("day07.txt"), "16,1,2\n").unwrap();
+        let resolver = Resolver { path: None, dir, embedded: false };
+        assert_eq!(resolver.resolve(2021, 7), Ok("16,1,2".to_string()));
+        assert!(resolver.resolve(2020, 7).is_err());
+    }
+
+    #[test]
+    fn test_resolve_missing() {
+        let resolver = Resolver { path: None, dir: temp_dir("missing"), embedded: false };
+        let error = resolver.resolve(2021, 3).unwrap_err();
+        assert!(error.starts_with("No input found for 2021 day 03"), "{}", error);
+
+        let resolver = Resolver { path: Some("does/not/exist.txt".to_string()), dir: temp_dir("missing"), embedded: true };
+        assert!(resolver.resolve(2021, 3).is_err());
+    }
+
+    #[cfg(feature = "embedded-inputs")]
+    #[test]
+    fn test_resolve_embedded_fallback() {
+        let resolver = Resolver { path: None, dir: temp_dir("embedded"), embedded: true };
+        assert_eq!(resolver.resolve(2021, 1).as_deref(), Ok(crate::day01::input()));
+        assert!(resolver.resolve(2020, 1).is_err());
+    }
+}
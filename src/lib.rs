@@ -0,0 +1,81 @@
+//! Solutions for [Advent of Code 2021](https://adventofcode.com/2021), and the runner around them.
+//!
+//! Every day is a module with a [`Day`](day01::Day) implementing [`Solver`](problem::Solver): it parses the
+//! puzzle input once into a domain type, and solves both parts from it.
+//!
+//! ```
+//! use adventofcode_2021::day01;
+//! use adventofcode_2021::problem::{Answer, Solver};
+//!
+//! let depths = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263";
+//! assert_eq!(day01::Day {}.solve_pt1(depths), Ok(Answer::Integer(7)));
+//! ```
+//!
+//! The domain types can also be used on their own, such as the risk map of day 15:
+//!
+//! ```
+//! use adventofcode_2021::day15;
+//! use adventofcode_2021::problem::Solver;
+//!
+//! let map = day15::Day {}.parse("116\n138\n213").unwrap();
+//! let (path, risk) = map.navigate(0, 8).unwrap();
+//! assert_eq!(path, vec![0, 3, 6, 7, 8]);
+//! assert_eq!(risk, 1 + 2 + 1 + 3);
+//! ```
+//!
+//! or the polymerization device of day 14:
+//!
+//! ```
+//! use adventofcode_2021::day14;
+//! use adventofcode_2021::problem::Solver;
+//!
+//! let device = day14::Day {}.parse("NN\n\nNN -> C\nNC -> B\nCN -> C").unwrap();
+//! // NN -> NCN
+//! assert_eq!(device.polymerize(1).unwrap(), vec![(1, 'C'), (2, 'N')]);
+//! ```
+//!
+//! All registered days are listed in [`PUZZLES`], and [`run`] is the command line interface of the binary.
+#![warn(missing_docs)]
+
+pub mod problem;
+pub mod registry;
+
+mod allocations;
+mod answers;
+mod app;
+mod bench;
+mod cli;
+mod client;
+mod crosscheck;
+mod examples;
+mod explain;
+mod history;
+mod input;
+mod repl;
+mod report;
+mod runner;
+mod scaffold;
+mod serve;
+mod submit;
+mod tui;
+mod watch;
+
+pub use app::run;
+
+registry::puzzles!(
+    day01,
+    day02,
+    day03,
+    day04,
+    day05,
+    day06,
+    day07,
+    day08,
+    day09,
+    day10,
+    day11,
+    day12,
+    day13,
+    day14,
+    day15,
+);
@@ -1,4 +1,281 @@
-pub trait Solver {
-    fn pt1(&self, inp: &str) -> String;
-    fn pt2(&self, inp: &str) -> String;
+//! The [`Solver`] trait implemented by every day, its [`Answer`]s and [`SolveError`]s, and parsing helpers.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The solver of a day: parses the puzzle input once, then solves each part from it. Solvers are run on threads of
+/// their own and solve the parts of a day in parallel, so they and their parsed input are `Send + Sync`.
+pub trait Solver: Send + Sync {
+    /// The input after parsing, shared by both parts.
+    type Parsed: Send + Sync;
+
+    /// Parses the puzzle input, reporting where it is malformed.
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError>;
+    /// Solves part 1.
+    fn pt1(&self, parsed: &Self::Parsed) -> Result<Answer, SolveError>;
+    /// Solves part 2.
+    fn pt2(&self, parsed: &Self::Parsed) -> Result<Answer, SolveError>;
+
+    /// Draws the parsed input, for the days with something to look at. Returns `None` by default.
+    fn visualize(&self, _parsed: &Self::Parsed) -> Option<String> {
+        None
+    }
+
+    /// Parses `inp` and solves part 1.
+    fn solve_pt1(&self, inp: &str) -> Result<Answer, SolveError> {
+        self.pt1(&self.parse(inp)?)
+    }
+
+    /// Parses `inp` and solves part 2.
+    fn solve_pt2(&self, inp: &str) -> Result<Answer, SolveError> {
+        self.pt2(&self.parse(inp)?)
+    }
+}
+
+/// Object safe counterpart of [`Solver`], so that solvers with different parsed types can be run side by side.
+pub trait DynSolver {
+    /// Parses the puzzle input, see [`Solver::parse`].
+    fn parse<'a>(&'a self, inp: &str) -> Result<Box<dyn Parsed + Send + Sync + 'a>, SolveError>;
+}
+
+/// A parsed input, ready to be solved.
+pub trait Parsed {
+    /// Solves part 1.
+    fn pt1(&self) -> Result<Answer, SolveError>;
+    /// Solves part 2.
+    fn pt2(&self) -> Result<Answer, SolveError>;
+    /// Draws the parsed input, see [`Solver::visualize`].
+    fn visualize(&self) -> Option<String>;
+}
+
+struct ParsedInput<'a, S: Solver> {
+    solver: &'a S,
+    parsed: S::Parsed,
+}
+
+impl<S: Solver> DynSolver for S {
+    fn parse<'a>(&'a self, inp: &str) -> Result<Box<dyn Parsed + Send + Sync + 'a>, SolveError> {
+        let parsed = Solver::parse(self, inp)?;
+        Ok(Box::new(ParsedInput { solver: self, parsed }))
+    }
+}
+
+impl<S: Solver> Parsed for ParsedInput<'_, S> {
+    fn pt1(&self) -> Result<Answer, SolveError> {
+        self.solver.pt1(&self.parsed)
+    }
+
+    fn pt2(&self) -> Result<Answer, SolveError> {
+        self.solver.pt2(&self.parsed)
+    }
+
+    fn visualize(&self) -> Option<String> {
+        self.solver.visualize(&self.parsed)
+    }
+}
+
+/// The answer to a part of a puzzle.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Answer {
+    /// A number, which most puzzles ask for.
+    Integer(i64),
+    /// A word or code.
+    Text(String),
+    /// Rows of an image, e.g. letters drawn with `#` and `.`.
+    Image(Vec<String>),
+}
+
+impl Answer {
+    /// Creates an image answer from newline separated rows.
+    pub fn image(rows: &str) -> Answer {
+        Answer::Image(rows.lines().map(|row| row.to_string()).collect())
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Integer(integer) => Display::fmt(integer, f),
+            Answer::Text(text) => f.pad(text),
+            Answer::Image(rows) => f.pad(&rows.join("\n")),
+        }
+    }
+}
+
+macro_rules! answer_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(integer: $t) -> Answer {
+                    Answer::Integer(i64::try_from(integer).expect("answer does not fit in an i64"))
+                }
+            }
+        )*
+    };
+}
+
+answer_from_integer!(i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Answer {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Answer {
+        Answer::Text(text.to_string())
+    }
+}
+
+/// Why a day could not be solved.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SolveError {
+    /// Part of the input could not be parsed. `line` and `column` are 1-based.
+    Parse {
+        /// The day of the puzzle.
+        day: u8,
+        /// The line of the input `text` starts on.
+        line: usize,
+        /// The column of the input `text` starts at.
+        column: usize,
+        /// The malformed part of the input.
+        text: String,
+        /// What was expected instead.
+        reason: String,
+    },
+    /// The input was parsed, but does not have an answer.
+    Unsolvable {
+        /// The day of the puzzle.
+        day: u8,
+        /// Why there is no answer.
+        reason: String,
+    },
+}
+
+impl SolveError {
+    /// Creates a parse error for `text`, which must be a slice of `inp`, locating it by its offset.
+    pub fn parse(day: u8, inp: &str, text: &str, reason: impl Into<String>) -> SolveError {
+        let (line, column) = position(inp, text);
+        SolveError::Parse {
+            day,
+            line,
+            column,
+            text: text.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Creates an error for an input without an answer.
+    pub fn unsolvable(day: u8, reason: impl Into<String>) -> SolveError {
+        SolveError::Unsolvable { day, reason: reason.into() }
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Parse { day, line, column, text, reason } => {
+                write!(f, "day {:02}, line {}, column {}: {} ('{}')", day, line, column, reason, text.escape_debug())
+            }
+            SolveError::Unsolvable { day, reason } => write!(f, "day {:02}: {}", day, reason),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+/// Parses `text`, a slice of `inp`, reporting its position in `inp` on failure.
+pub fn parse<T>(day: u8, inp: &str, text: &str) -> Result<T, SolveError>
+    where T: FromStr, T::Err: Display {
+    text.parse::<T>().map_err(|e| SolveError::parse(day, inp, text, e.to_string()))
+}
+
+/// Splits `text`, a slice of `inp`, on the first occurrence of `delimiter`.
+pub fn split_once<'a>(day: u8, inp: &str, text: &'a str, delimiter: &str) -> Result<(&'a str, &'a str), SolveError> {
+    text.split_once(delimiter)
+        .ok_or_else(|| SolveError::parse(day, inp, text, format!("expected '{}'", delimiter.escape_default())))
+}
+
+/// Returns the 1-based line and column at which `text` starts within `inp`.
+///
+/// `text` is located by its address, so it must be borrowed from `inp`. Anything else is reported as line 0,
+/// column 0.
+fn position(inp: &str, text: &str) -> (usize, usize) {
+    let start = inp.as_ptr() as usize;
+    let offset = (text.as_ptr() as usize).wrapping_sub(start);
+    if offset > inp.len() {
+        return (0, 0);
+    }
+    let before = &inp[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer_display() {
+        assert_eq!(Answer::from(1715).to_string(), "1715");
+        assert_eq!(Answer::from(-3_i64).to_string(), "-3");
+        assert_eq!(Answer::from("NNCB").to_string(), "NNCB");
+        assert_eq!(Answer::image("#.#\n.#.\n").to_string(), "#.#\n.#.");
+        assert_eq!(format!("{:>6}", Answer::from(1715)), "  1715");
+        assert_eq!(format!("{:>6}", Answer::from("NNCB")), "  NNCB");
+    }
+
+    #[test]
+    fn test_answer_eq() {
+        assert_eq!(Answer::from(2911561572630_u64), Answer::Integer(2911561572630));
+        assert_eq!(Answer::from(5_usize), Answer::from(5_i16));
+        assert_ne!(Answer::from(5), Answer::from("5"));
+        assert_eq!(Answer::image("#.#\n.#."), Answer::Image(vec!["#.#".to_string(), ".#.".to_string()]));
+    }
+
+    #[test]
+    fn test_position() {
+        let inp = "199\n200\n2x8";
+        let line = inp.lines().last().unwrap();
+        assert_eq!(position(inp, inp), (1, 1));
+        assert_eq!(position(inp, line), (3, 1));
+        assert_eq!(position(inp, &line[1..]), (3, 2));
+        let elsewhere = String::from("2x8");
+        assert_eq!(position(inp, &elsewhere), (0, 0));
+    }
+
+    #[test]
+    fn test_parse() {
+        let inp = "199\n200\n2x8";
+        let line = inp.lines().last().unwrap();
+        assert_eq!(parse::<i32>(1, inp, "200"), Ok(200));
+        assert_eq!(
+            parse::<i32>(1, inp, line),
+            Err(SolveError::Parse {
+                day: 1,
+                line: 3,
+                column: 1,
+                text: "2x8".to_string(),
+                reason: "invalid digit found in string".to_string(),
+            })
+        );
+        assert_eq!(
+            parse::<i32>(1, inp, line).unwrap_err().to_string(),
+            "day 01, line 3, column 1: invalid digit found in string ('2x8')"
+        );
+    }
+
+    #[test]
+    fn test_split_once() {
+        let inp = "start-A\nA=end";
+        let line = inp.lines().last().unwrap();
+        assert_eq!(split_once(12, inp, inp.lines().next().unwrap(), "-"), Ok(("start", "A")));
+        assert_eq!(
+            split_once(12, inp, line, "-").unwrap_err().to_string(),
+            "day 12, line 2, column 1: expected '-' ('A=end')"
+        );
+    }
 }
@@ -0,0 +1,164 @@
+//! The registry of day solvers, and the macros day modules register themselves with.
+
+use crate::problem::DynSolver;
+
+/// A registered solver, along with its embedded input and metadata.
+pub struct Puzzle {
+    /// The event year, such as 2021.
+    pub year: u16,
+    /// The day of the event, from 1 to 25.
+    pub day: u8,
+    /// The title of the puzzle.
+    pub title: &'static str,
+    /// The solver of both parts, shared with the threads that run it.
+    pub solver: &'static (dyn DynSolver + Sync),
+    /// Alternative solvers, such as a brute force and a smarter approach, besides the default `solver`.
+    pub variants: &'static [Variant],
+    /// The embedded puzzle input, if built with the `embedded-inputs` feature.
+    pub input: Option<fn() -> &'static str>,
+}
+
+/// A named alternative solver of a puzzle.
+pub struct Variant {
+    /// The name to select the variant with, such as `median`.
+    pub name: &'static str,
+    /// The solver of both parts.
+    pub solver: &'static (dyn DynSolver + Sync),
+}
+
+/// The name of the default solver of every puzzle.
+pub const DEFAULT_VARIANT: &str = "default";
+
+impl Puzzle {
+    /// Returns the solver named `name`, where [`DEFAULT_VARIANT`] is the default solver.
+    pub fn variant(&self, name: &str) -> Option<&'static (dyn DynSolver + Sync)> {
+        if name == DEFAULT_VARIANT {
+            return Some(self.solver);
+        }
+        self.variants.iter().find(|variant| variant.name == name).map(|variant| variant.solver)
+    }
+
+    /// Returns the names of all solvers, the default first.
+    pub fn variant_names(&self) -> Vec<&'static str> {
+        [DEFAULT_VARIANT].into_iter().chain(self.variants.iter().map(|variant| variant.name)).collect()
+    }
+}
+
+/// Registers a day module's solver, and any named variants, as `PUZZLE`, and defines its embedded `input()`.
+///
+/// ```ignore
+/// puzzle!(year: 2021, day: DAY, title: "Sonar Sweep", solver: Day {}, input: "input01.txt");
+/// puzzle!(year: 2021, day: DAY, title: "...", solver: Day {}, variants: ["median" => Median {}], input: "input07.txt");
+/// ```
+macro_rules! puzzle {
+    (
+        year: $year:expr, day: $day:expr, title: $title:expr, solver: $solver:expr,
+        $(variants: [$($name:literal => $variant:expr),* $(,)?],)?
+        input: $input:literal $(,)?
+    ) => {
+        pub(crate) const PUZZLE: $crate::registry::Puzzle = $crate::registry::Puzzle {
+            year: $year,
+            day: $day,
+            title: $title,
+            solver: &$solver,
+            variants: &[$($($crate::registry::Variant { name: $name, solver: &$variant }),*)?],
+            input: EMBEDDED_INPUT,
+        };
+
+        #[cfg(feature = "embedded-inputs")]
+        const EMBEDDED_INPUT: Option<fn() -> &'static str> = Some(input);
+        #[cfg(not(feature = "embedded-inputs"))]
+        const EMBEDDED_INPUT: Option<fn() -> &'static str> = None;
+
+        #[cfg(any(test, feature = "embedded-inputs"))]
+        pub(crate) fn input() -> &'static str {
+            include_str!($input).trim()
+        }
+    };
+}
+
+/// Declares the day modules and collects their `PUZZLE`s into `PUZZLES`, in the listed order.
+macro_rules! puzzles {
+    ($($module:ident),* $(,)?) => {
+        $(pub mod $module;)*
+
+        /// Every registered puzzle, by day.
+        pub const PUZZLES: &[$crate::registry::Puzzle] = &[$($module::PUZZLE),*];
+    };
+}
+
+pub(crate) use {puzzle, puzzles};
+
+/// Returns the registered puzzle of `day` in `year`.
+pub fn find(year: u16, day: u8) -> Option<&'static Puzzle> {
+    crate::PUZZLES.iter().find(|puzzle| puzzle.year == year && puzzle.day == day)
+}
+
+/// Returns the years with registered puzzles, in ascending order.
+pub fn years() -> Vec<u16> {
+    let mut years: Vec<u16> = crate::PUZZLES.iter().map(|puzzle| puzzle.year).collect();
+    years.sort_unstable();
+    years.dedup();
+    years
+}
+
+/// Returns the most recent year with registered puzzles, the default year of the command line.
+pub fn latest_year() -> u16 {
+    years().last().copied().expect("at least one registered puzzle")
+}
+
+/// Returns the registered days of `year`, in ascending order.
+pub fn days(year: u16) -> Vec<u8> {
+    let mut days: Vec<u8> = crate::PUZZLES.iter().filter(|puzzle| puzzle.year == year).map(|puzzle| puzzle.day).collect();
+    days.sort_unstable();
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_puzzles_are_unique() {
+        let mut puzzles: Vec<(u16, u8)> = crate::PUZZLES.iter().map(|puzzle| (puzzle.year, puzzle.day)).collect();
+        puzzles.sort_unstable();
+        puzzles.dedup();
+        assert_eq!(puzzles.len(), crate::PUZZLES.len());
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(2021, 12).map(|puzzle| puzzle.title), Some("Passage Pathing"));
+        assert!(find(2021, 26).is_none());
+        assert!(find(2020, 12).is_none());
+    }
+
+    #[test]
+    fn test_variants() {
+        let puzzle = find(2021, 7).unwrap();
+        assert_eq!(puzzle.variant_names(), vec![DEFAULT_VARIANT, "median"]);
+        assert!(puzzle.variant("median").is_some());
+        assert!(puzzle.variant("quantum").is_none());
+        assert_eq!(find(2021, 1).unwrap().variant_names(), vec![DEFAULT_VARIANT]);
+
+        // Every variant has a unique name.
+        for puzzle in crate::PUZZLES {
+            let mut names = puzzle.variant_names();
+            names.sort_unstable();
+            names.dedup();
+            assert_eq!(names.len(), puzzle.variants.len() + 1, "day {}", puzzle.day);
+        }
+    }
+
+    #[test]
+    fn test_years() {
+        // Derived from the registered puzzles, so that scaffolding a day keeps this passing.
+        let registered = years();
+        assert!(registered.contains(&2021) && registered.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", registered);
+        assert_eq!(Some(&latest_year()), registered.last());
+        let registered = days(2021);
+        assert!((1..=15).all(|day| registered.contains(&day)) && registered.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", registered);
+        assert_eq!(registered.len(), crate::PUZZLES.iter().filter(|puzzle| puzzle.year == 2021).count());
+        assert!(days(2020).is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use std::path::PathBuf;
+
+use rustyline::{Context, Editor, Helper};
+
+use crate::input::Resolver;
+use crate::problem::Solver;
+use crate::registry::{self, Puzzle};
+use crate::{day11, day13, day14};
+
+const COMMANDS: [&str; 11] = ["load", "show", "pt1", "pt2", "fold", "simulate", "polymerize", "days", "help", "quit", "exit"];
+
+const HELP: &str = "Commands:
+    load <day> [path]     parse the input of a day, such as `load day13` or `load 13 example.txt`
+    show                  print the state of the loaded day
+    pt1, pt2              solve a part of the loaded input
+    fold [n]              make the next n folds (default 1) of the day 13 manual
+    simulate <steps>      let the day 11 octopuses flash for a number of steps
+    polymerize <steps>    count the elements of the day 14 polymer after a number of steps
+    days                  list the days of the year
+    help                  show this help
+    quit                  leave, as does Ctrl-D";
+
+/// The most steps `polymerize` takes, as the counts of elements double with every step.
+const MAX_POLYMERIZE_STEPS: usize = 50;
+
+/// The domain type of a loaded day, for the days with operations of their own.
+enum State {
+    Grid(day11::Grid),
+    Manual(day13::Manual),
+    Device(day14::PolymerizationDevice),
+    /// A day without operations, which can only be solved.
+    Input,
+}
+
+struct Loaded {
+    day: u8,
+    inp: String,
+    state: State,
+}
+
+/// The loaded day of a repl, and the commands on it.
+pub(crate) struct Session {
+    year: u16,
+    loaded: Option<Loaded>,
+}
+
+impl Session {
+    pub(crate) fn new(year: u16) -> Session {
+        Session { year, loaded: None }
+    }
+
+    fn prompt(&self) -> String {
+        match &self.loaded {
+            Some(loaded) => format!("day{:02}> ", loaded.day),
+            None => "> ".to_string(),
+        }
+    }
+
+    /// Executes the command `line`, returning what to print.
+    pub(crate) fn execute(&mut self, line: &str) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => Ok(String::new()),
+            ["help"] => Ok(HELP.to_string()),
+            ["days"] => Ok(registry::days(self.year)
+                .iter()
+                .map(|day| format!("day{:02}: {}", day, registry::find(self.year, *day).unwrap().title))
+                .collect::<Vec<String>>()
+                .join("\n")),
+            ["load", day] => self.load(day, None),
+            ["load", day, path] => self.load(day, Some(path)),
+            ["show"] => match &self.loaded()?.state {
+                State::Grid(grid) => Ok(grid.to_string()),
+                State::Manual(manual) => Ok(manual.to_string()),
+                State::Device(device) => Ok(device.to_string()),
+                State::Input => Ok(format!("day{:02} has no state to show, solve it with pt1 or pt2", self.loaded()?.day)),
+            },
+            ["pt1"] => self.solve(1),
+            ["pt2"] => self.solve(2),
+            ["fold"] => self.fold(1),
+            ["fold", folds] => self.fold(parse_count("fold", folds)?),
+            ["simulate", steps] => {
+                let steps: u8 = parse_count("simulate", steps)?;
+                match &mut self.loaded_mut()?.state {
+                    State::Grid(grid) => {
+                        grid.simulate(steps);
+                        Ok(format!("{}\nflashes: {}", grid, grid.flashes()))
+                    }
+                    _ => Err("simulate needs the octopuses of day 11".to_string()),
+                }
+            }
+            ["polymerize", steps] => {
+                let steps: usize = parse_count("polymerize", steps)?;
+                if steps > MAX_POLYMERIZE_STEPS {
+                    return Err(format!("polymerize takes at most {} steps", MAX_POLYMERIZE_STEPS));
+                }
+                match &self.loaded()?.state {
+                    State::Device(device) => {
+                        let elements = device.polymerize(steps).map_err(|e| e.to_string())?;
+                        let mut lines: Vec<String> = elements.iter().map(|(count, element)| format!("{}: {}", element, count)).collect();
+                        lines.push(format!("most - least common: {}", elements[elements.len() - 1].0 - elements[0].0));
+                        Ok(lines.join("\n"))
+                    }
+                    _ => Err("polymerize needs the polymerization device of day 14".to_string()),
+                }
+            }
+            [command, ..] if COMMANDS.contains(command) => Err(format!("Invalid arguments for {}, see help", command)),
+            [command, ..] => Err(format!("Unknown command: '{}', see help", command)),
+        }
+    }
+
+    fn loaded(&self) -> Result<&Loaded, String> {
+        self.loaded.as_ref().ok_or_else(|| "No day loaded, use load <day>".to_string())
+    }
+
+    fn loaded_mut(&mut self) -> Result<&mut Loaded, String> {
+        self.loaded.as_mut().ok_or_else(|| "No day loaded, use load <day>".to_string())
+    }
+
+    /// Loads the puzzle input of `day`, such as `day13` or `13`, or the input at `path`.
+    fn load(&mut self, day: &str, path: Option<&str>) -> Result<String, String> {
+        let day: u8 = day.strip_prefix("day").unwrap_or(day).parse().map_err(|_| format!("Invalid day: '{}'", day))?;
+        let puzzle = registry::find(self.year, day).ok_or_else(|| format!("Unknown day of {}: {}", self.year, day))?;
+        if path == Some("-") {
+            return Err("The repl reads its commands from stdin, load the input from a file".to_string());
+        }
+        let inp = Resolver::from_env(path.map(String::from)).resolve(self.year, day)?;
+        let state = if registered_as(puzzle, &day11::PUZZLE) {
+            State::Grid(day11::Day {}.parse(&inp).map_err(|e| e.to_string())?)
+        } else if registered_as(puzzle, &day13::PUZZLE) {
+            State::Manual(day13::Day {}.parse(&inp).map_err(|e| e.to_string())?)
+        } else if registered_as(puzzle, &day14::PUZZLE) {
+            State::Device(day14::Day {}.parse(&inp).map_err(|e| e.to_string())?)
+        } else {
+            puzzle.solver.parse(&inp).map_err(|e| e.to_string())?;
+            State::Input
+        };
+        self.loaded = Some(Loaded { day, inp, state });
+        Ok(format!("loaded {} day{:02}: {}", self.year, day, puzzle.title))
+    }
+
+    /// Solves `part` of the loaded input, regardless of the operations on its state.
+    fn solve(&self, part: u8) -> Result<String, String> {
+        let loaded = self.loaded()?;
+        let puzzle = registry::find(self.year, loaded.day).expect("loaded day");
+        let parsed = puzzle.solver.parse(&loaded.inp).map_err(|e| e.to_string())?;
+        let answer = if part == 1 { parsed.pt1() } else { parsed.pt2() };
+        answer.map(|answer| answer.to_string()).map_err(|e| e.to_string())
+    }
+
+    fn fold(&mut self, folds: usize) -> Result<String, String> {
+        let manual = match &mut self.loaded_mut()?.state {
+            State::Manual(manual) => manual,
+            _ => return Err("fold needs the manual of day 13".to_string()),
+        };
+        let mut lines = vec![];
+        for _ in 0..folds {
+            match manual.fold() {
+                Some((direction, line)) => lines.push(format!("folded along {}={}", direction, line)),
+                None => {
+                    lines.push("no folds left".to_string());
+                    break;
+                }
+            }
+        }
+        lines.push(manual.to_string());
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Whether `puzzle` is the puzzle the day module of `module` registered, whose domain type the repl operates on.
+fn registered_as(puzzle: &Puzzle, module: &Puzzle) -> bool {
+    puzzle.year == module.year && puzzle.day == module.day
+}
+
+fn parse_count<T: std::str::FromStr>(command: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Invalid count for {}: '{}'", command, value))
+}
+
+/// Returns where the word before `pos` in `line` starts, and its completions: the commands for the first word, and
+/// the day names of `year` after `load`.
+fn complete(year: u16, line: &str, pos: usize) -> (usize, Vec<String>) {
+    let line = &line[..pos];
+    let start = line.rfind(' ').map(|i| i + 1).unwrap_or(0);
+    let previous: Vec<&str> = line[..start].split_whitespace().collect();
+    let candidates: Vec<String> = match previous.as_slice() {
+        [] => COMMANDS.iter().map(|command| command.to_string()).collect(),
+        ["load"] => registry::days(year).iter().map(|day| format!("day{:02}", day)).collect(),
+        _ => vec![],
+    };
+    let word = &line[start..];
+    (start, candidates.into_iter().filter(|candidate| candidate.starts_with(word)).collect())
+}
+
+/// Completes the commands and day names of the repl.
+struct Completion {
+    year: u16,
+}
+
+impl Completer for Completion {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete(self.year, line, pos))
+    }
+}
+
+impl Hinter for Completion {
+    type Hint = String;
+}
+
+impl Highlighter for Completion {}
+
+impl Validator for Completion {}
+
+impl Helper for Completion {}
+
+/// The file the commands are kept in between sessions, `$XDG_STATE_HOME/adventofcode/repl_history` or
+/// `~/.local/state/adventofcode/repl_history`.
+fn history_path() -> Option<PathBuf> {
+    let state = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state")))?;
+    Some(state.join("adventofcode").join("repl_history"))
+}
+
+/// Reads and executes commands on the days of `year` until Ctrl-D or `quit`, with the commands of earlier sessions
+/// in the history. Returns false if the terminal failed.
+pub(crate) fn run(year: u16) -> bool {
+    let mut editor: Editor<Completion, DefaultHistory> = match Editor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("Failed to start the repl: {}", e);
+            return false;
+        }
+    };
+    editor.set_helper(Some(Completion { year }));
+    let history = history_path();
+    if let Some(path) = &history {
+        match editor.load_history(path) {
+            Err(ReadlineError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => eprintln!("Failed to read the history '{}': {}", path.display(), e),
+            Ok(()) => {}
+        }
+    }
+    let mut session = Session::new(year);
+    println!("{} repl, type help for the commands", year);
+    let ok = loop {
+        match editor.readline(&session.prompt()) {
+            Ok(line) => {
+                let line = line.trim();
+                if !line.is_empty() {
+                    let _ = editor.add_history_entry(line);
+                }
+                if line == "quit" || line == "exit" {
+                    break true;
+                }
+                match session.execute(line) {
+                    Ok(output) if output.is_empty() => {}
+                    Ok(output) => println!("{}", output),
+                    Err(message) => eprintln!("{}", message),
+                }
+            }
+            // Ctrl-C abandons the line being edited.
+            Err(ReadlineError::Interrupted) => {}
+            Err(ReadlineError::Eof) => break true,
+            Err(e) => {
+                eprintln!("{}", e);
+                break false;
+            }
+        }
+    };
+    if let Some(path) = &history {
+        let saved = match path.parent() {
+            Some(dir) => std::fs::create_dir_all(dir).map_err(ReadlineError::Io),
+            None => Ok(()),
+        };
+        if let Err(e) = saved.and_then(|()| editor.save_history(path)) {
+            eprintln!("Failed to save the history '{}': {}", path.display(), e);
+        }
+    }
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_input(name: &str, inp: &str) -> String {
+        let path = std::env::temp_dir().join(format!("adventofcode-2021-repl-{}-{}.txt", name, std::process::id()));
+        std::fs::write(&path, inp).unwrap();
+        path.display().to_string()
+    }
+
+    #[test]
+    fn test_complete() {
+        assert_eq!(complete(2021, "po", 2), (0, vec!["polymerize".to_string()]));
+        // Whichever days are registered, scaffolded ones included.
+        let days: Vec<String> = registry::days(2021).iter().map(|day| format!("day{:02}", day)).filter(|day| day.starts_with("day1")).collect();
+        assert!(days.contains(&"day15".to_string()));
+        assert_eq!(complete(2021, "load day1", 9), (5, days));
+        assert_eq!(complete(2021, "load day13 x", 12), (11, vec![]));
+        assert_eq!(complete(2021, "p", 1).1, vec!["pt1", "pt2", "polymerize"]);
+    }
+
+    #[test]
+    fn test_fold() {
+        let path = temp_input("fold", "6,10\n0,14\n9,10\n0,3\n10,4\n4,11\n6,0\n6,12\n4,1\n0,13\n10,12\n3,4\n3,0\n8,4\n1,10\n2,14\n8,10\n9,0\n\nfold along y=7\nfold along x=5");
+        let mut session = Session::new(2021);
+        assert!(session.execute("fold").is_err());
+        assert_eq!(session.execute(&format!("load day13 {}", path)), Ok("loaded 2021 day13: Transparent Origami".to_string()));
+        assert_eq!(session.prompt(), "day13> ");
+        assert_eq!(session.execute("fold 3"), Ok("folded along y=7\nfolded along x=5\nno folds left\n#####\n#...#\n#...#\n#...#\n#####".to_string()));
+        // Solving starts over from the loaded input.
+        assert_eq!(session.execute("pt1"), Ok("17".to_string()));
+        assert!(session.execute("simulate 1").is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_simulate() {
+        let path = temp_input("simulate", "11111\n19991\n19191\n19991\n11111");
+        let mut session = Session::new(2021);
+        session.execute(&format!("load 11 {}", path)).unwrap();
+        assert_eq!(session.execute("simulate 1"), Ok("34543\n40004\n50005\n40004\n34543\nflashes: 9".to_string()));
+        assert_eq!(session.execute("show"), Ok("34543\n40004\n50005\n40004\n34543".to_string()));
+        assert!(session.execute("simulate 256").is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_polymerize() {
+        let path = temp_input("polymerize", "NN\n\nNN -> C\nNC -> B\nCN -> C");
+        let mut session = Session::new(2021);
+        session.execute(&format!("load day14 {}", path)).unwrap();
+        assert_eq!(session.execute("show"), Ok("NN\n\nCN -> C\nNC -> B\nNN -> C".to_string()));
+        assert_eq!(session.execute("polymerize 1"), Ok("C: 1\nN: 2\nmost - least common: 1".to_string()));
+        assert!(session.execute("polymerize 51").is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_execute_invalid() {
+        let mut session = Session::new(2021);
+        assert!(session.execute("").unwrap().is_empty());
+        assert!(session.execute("dance").is_err());
+        assert!(session.execute("load").is_err());
+        assert!(session.execute("load day26").is_err());
+        assert!(session.execute("load 13 -").is_err());
+        assert!(session.execute("show").is_err());
+        // Only the days registered by the modules of their domain types are loaded as them.
+        assert!(Session::new(2020).execute("load day13").is_err());
+    }
+}
@@ -0,0 +1,399 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::allocations::Allocations;
+use crate::answers::{Answers, Verdict};
+use crate::problem::Answer;
+use crate::runner::{DayResult, Failure, StepError};
+
+/// How the results of a run are printed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum Format {
+    /// A human readable table, printed as each day finishes.
+    #[default]
+    Table,
+    /// A JSON array with one object per part.
+    Json,
+    /// CSV with a header and one row per part.
+    Csv,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Status {
+    Ok,
+    Error,
+    Panic,
+    Timeout,
+}
+
+impl Status {
+    fn of(error: &StepError) -> Status {
+        match error {
+            StepError::Solve(_) => Status::Error,
+            StepError::Panic(_) => Status::Panic,
+            StepError::Timeout(_) => Status::Timeout,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Error => "error",
+            Status::Panic => "panic",
+            Status::Timeout => "timeout",
+        }
+    }
+}
+
+/// One row of a machine readable report.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Record<'a> {
+    pub(crate) year: u16,
+    pub(crate) day: u8,
+    pub(crate) part: u8,
+    pub(crate) answer: Option<&'a Answer>,
+    pub(crate) duration: Duration,
+    pub(crate) allocations: Option<Allocations>,
+    pub(crate) status: Status,
+    pub(crate) error: Option<String>,
+}
+
+/// Prints the table rows of one day: its parse time, then each part, checked against `answers` if given.
+pub(crate) fn print_table(result: &DayResult, answers: Option<&Answers>) {
+    let day = result.day;
+    match &result.failure {
+        Some(Failure::Input(message)) => {
+            eprintln!("{}", message);
+            return;
+        }
+        Some(Failure::Parse(e)) => {
+            println!("day{:02} - parse: {:>13} ({}) {}", day, e.label(), cost(result.parse, result.parse_allocations), e);
+            return;
+        }
+        None => println!("day{:02} - parse: {:>13} ({})", day, "", cost(result.parse, result.parse_allocations)),
+    }
+
+    for part in &result.parts {
+        let verdict = match (&part.answer, answers) {
+            (Ok(answer), Some(answers)) => format!(" {}", verdict(&answers.verify(day, part.part, answer))),
+            _ => String::new(),
+        };
+        let cost = cost(part.elapsed, part.allocations);
+        match &part.answer {
+            Ok(Answer::Image(rows)) => {
+                println!("day{:02} - pt{}: {:>15} ({}){}", day, part.part, image_size(rows), cost, verdict);
+                // Indent the rows to line up with the answer column, after "dayNN - ptN: ".
+                for row in rows {
+                    println!("{:13}{}", "", row);
+                }
+            }
+            Ok(answer) => println!("day{:02} - pt{}: {:>15} ({}){}", day, part.part, answer, cost, verdict),
+            Err(e) => println!("day{:02} - pt{}: {:>15} ({}) {}", day, part.part, e.label(), cost, e),
+        }
+    }
+}
+
+/// The time a step took, and its allocations if they are counted.
+fn cost(elapsed: Duration, allocations: Option<Allocations>) -> String {
+    match allocations {
+        Some(allocations) => format!("{:.2?}, {}", elapsed, allocations),
+        None => format!("{:.2?}", elapsed),
+    }
+}
+
+/// The size of an image answer, shown in its place in tables.
+pub(crate) fn image_size(rows: &[String]) -> String {
+    format!("{}x{} image", rows.first().map(|row| row.len()).unwrap_or(0), rows.len())
+}
+
+pub(crate) fn verdict(verdict: &Verdict) -> String {
+    match verdict {
+        Verdict::Pass => "PASS".to_string(),
+        Verdict::Fail { expected } => format!("FAIL (expected {})", expected),
+        Verdict::Unknown => "UNKNOWN".to_string(),
+    }
+}
+
+/// Counts the passed, failed and unknown answers in `results`.
+pub(crate) fn verify(results: &[DayResult], answers: &Answers) -> (usize, usize, usize) {
+    let mut counts = (0, 0, 0);
+    for result in results {
+        for part in &result.parts {
+            match part.answer.as_ref().map(|answer| answers.verify(result.day, part.part, answer)) {
+                Ok(Verdict::Pass) => counts.0 += 1,
+                Ok(Verdict::Fail { .. }) => counts.1 += 1,
+                Ok(Verdict::Unknown) => counts.2 += 1,
+                Err(_) => {}
+            }
+        }
+    }
+    counts
+}
+
+/// Flattens `results` into one record per part. Days that failed get an error record for each of `parts`.
+pub(crate) fn records<'a>(results: &'a [DayResult], parts: &[u8]) -> Vec<Record<'a>> {
+    let mut records = vec![];
+    for result in results {
+        if let Some(failure) = &result.failure {
+            let (duration, allocations, status, error) = match failure {
+                Failure::Input(message) => (Duration::ZERO, None, Status::Error, message.clone()),
+                Failure::Parse(e) => (result.parse, result.parse_allocations, Status::of(e), e.to_string()),
+            };
+            for &part in parts {
+                records.push(Record {
+                    year: result.year,
+                    day: result.day,
+                    part,
+                    answer: None,
+                    duration,
+                    allocations,
+                    status,
+                    error: Some(error.clone()),
+                });
+            }
+            continue;
+        }
+        for part in &result.parts {
+            records.push(match &part.answer {
+                Ok(answer) => Record {
+                    year: result.year,
+                    day: result.day,
+                    part: part.part,
+                    answer: Some(answer),
+                    duration: part.elapsed,
+                    allocations: part.allocations,
+                    status: Status::Ok,
+                    error: None,
+                },
+                Err(e) => Record {
+                    year: result.year,
+                    day: result.day,
+                    part: part.part,
+                    answer: None,
+                    duration: part.elapsed,
+                    allocations: part.allocations,
+                    status: Status::of(e),
+                    error: Some(e.to_string()),
+                },
+            });
+        }
+    }
+    records
+}
+
+/// Formats `records` as a JSON array. Integer answers are numbers, other answers strings.
+pub(crate) fn json(records: &[Record]) -> String {
+    let mut out = String::from("[");
+    for (i, record) in records.iter().enumerate() {
+        let answer = record.answer.map(json_answer).unwrap_or_else(|| "null".to_string());
+        let error = record.error.as_deref().map(json_string).unwrap_or_else(|| "null".to_string());
+        let [allocations, allocated_bytes, peak_bytes] = allocation_fields(record.allocations, "null");
+        write!(
+            out,
+            "{}\n  {{\"year\": {}, \"day\": {}, \"part\": {}, \"answer\": {}, \"duration_ns\": {}, \"allocations\": {}, \
+             \"allocated_bytes\": {}, \"peak_bytes\": {}, \"status\": \"{}\", \"error\": {}}}",
+            if i == 0 { "" } else { "," },
+            record.year,
+            record.day,
+            record.part,
+            answer,
+            record.duration.as_nanos(),
+            allocations,
+            allocated_bytes,
+            peak_bytes,
+            record.status.as_str(),
+            error,
+        )
+        .unwrap();
+    }
+    out.push_str("\n]");
+    out
+}
+
+/// Formats `records` as CSV, with a header row.
+pub(crate) fn csv(records: &[Record]) -> String {
+    let mut out = String::from("year,day,part,answer,duration_ns,allocations,allocated_bytes,peak_bytes,status,error\n");
+    for record in records {
+        let [allocations, allocated_bytes, peak_bytes] = allocation_fields(record.allocations, "");
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{}",
+            record.year,
+            record.day,
+            record.part,
+            csv_field(&record.answer.map(answer_text).unwrap_or_default()),
+            record.duration.as_nanos(),
+            allocations,
+            allocated_bytes,
+            peak_bytes,
+            record.status.as_str(),
+            csv_field(record.error.as_deref().unwrap_or_default()),
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// The allocation count, bytes and peak bytes of a record, or `missing` when they were not counted.
+fn allocation_fields(allocations: Option<Allocations>, missing: &str) -> [String; 3] {
+    match allocations {
+        Some(allocations) => [allocations.count, allocations.bytes, allocations.peak].map(|n| n.to_string()),
+        None => [missing, missing, missing].map(String::from),
+    }
+}
+
+/// The answer as plain text, with image rows separated by newlines.
+fn answer_text(answer: &Answer) -> String {
+    match answer {
+        Answer::Image(rows) => rows.join("\n"),
+        answer => answer.to_string(),
+    }
+}
+
+/// The answer as a JSON value: integers as numbers, other answers as strings.
+pub(crate) fn json_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Integer(integer) => integer.to_string(),
+        answer => json_string(&answer_text(answer)),
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Quotes `s` if it contains a separator, quote or newline.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::SolveError;
+    use crate::runner::PartResult;
+
+    fn results() -> Vec<DayResult> {
+        vec![
+            DayResult {
+                year: 2021,
+                day: 13,
+                parse: Duration::from_micros(5),
+                parse_allocations: None,
+                failure: None,
+                parts: vec![
+                    PartResult { part: 1, elapsed: Duration::from_nanos(1500), allocations: None, answer: Ok(Answer::Integer(17)) },
+                    PartResult { part: 2, elapsed: Duration::from_nanos(2500), allocations: Some(Allocations { count: 3, bytes: 96, peak: 64 }), answer: Ok(Answer::image("#.\n.#")) },
+                ],
+            },
+            DayResult {
+                year: 2021,
+                day: 14,
+                parse: Duration::from_nanos(700),
+                parse_allocations: None,
+                failure: Some(Failure::Parse(StepError::Solve(SolveError::unsolvable(14, "no \"rules\"")))),
+                parts: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_records() {
+        let results = results();
+        let records = records(&results, &[1, 2]);
+        assert_eq!(records.len(), 4);
+        assert_eq!(
+            records[3],
+            Record {
+                year: 2021,
+                day: 14,
+                part: 2,
+                answer: None,
+                duration: Duration::from_nanos(700),
+                allocations: None,
+                status: Status::Error,
+                error: Some("day 14: no \"rules\"".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let results = results();
+        assert_eq!(
+            json(&records(&results, &[1, 2])),
+            r##"[
+  {"year": 2021, "day": 13, "part": 1, "answer": 17, "duration_ns": 1500, "allocations": null, "allocated_bytes": null, "peak_bytes": null, "status": "ok", "error": null},
+  {"year": 2021, "day": 13, "part": 2, "answer": "#.\n.#", "duration_ns": 2500, "allocations": 3, "allocated_bytes": 96, "peak_bytes": 64, "status": "ok", "error": null},
+  {"year": 2021, "day": 14, "part": 1, "answer": null, "duration_ns": 700, "allocations": null, "allocated_bytes": null, "peak_bytes": null, "status": "error", "error": "day 14: no \"rules\""},
+  {"year": 2021, "day": 14, "part": 2, "answer": null, "duration_ns": 700, "allocations": null, "allocated_bytes": null, "peak_bytes": null, "status": "error", "error": "day 14: no \"rules\""}
+]"##
+        );
+        assert_eq!(json(&[]), "[\n]");
+    }
+
+    #[test]
+    fn test_csv() {
+        let results = results();
+        assert_eq!(
+            csv(&records(&results, &[1, 2])),
+            "year,day,part,answer,duration_ns,allocations,allocated_bytes,peak_bytes,status,error
+2021,13,1,17,1500,,,,ok,
+2021,13,2,\"#.\n.#\",2500,3,96,64,ok,
+2021,14,1,,700,,,,error,\"day 14: no \"\"rules\"\"\"
+2021,14,2,,700,,,,error,\"day 14: no \"\"rules\"\"\"
+"
+        );
+    }
+
+    #[test]
+    fn test_csv_statuses() {
+        let results = vec![DayResult {
+            year: 2021,
+            day: 12,
+            parse: Duration::from_nanos(100),
+            parse_allocations: None,
+            failure: None,
+            parts: vec![
+                PartResult { part: 1, elapsed: Duration::from_nanos(200), allocations: None, answer: Err(StepError::Panic("oops".to_string())) },
+                PartResult { part: 2, elapsed: Duration::from_secs(1), allocations: None, answer: Err(StepError::Timeout(Duration::from_secs(1))) },
+            ],
+        }];
+        assert_eq!(
+            csv(&records(&results, &[1, 2])),
+            "year,day,part,answer,duration_ns,allocations,allocated_bytes,peak_bytes,status,error
+2021,12,1,,200,,,,panic,panicked: oops
+2021,12,2,,1000000000,,,,timeout,no result within 1.00s
+"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::parse("[day13]\npart1 = 17\npart2 = \"#.\"").unwrap();
+        assert_eq!(verify(&results(), &answers), (1, 1, 0));
+        assert_eq!(verify(&results(), &Answers::default()), (0, 0, 2));
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a\"b\\c\u{1}"), r#""a\"b\\c\u0001""#);
+    }
+}
@@ -0,0 +1,537 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Condvar, Mutex, Once};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::allocations::{self, Allocations};
+use crate::input::Resolver;
+use crate::problem::{Answer, DynSolver, Parsed, SolveError};
+use crate::registry;
+
+/// Why a day has no answers.
+#[derive(Debug)]
+pub(crate) enum Failure {
+    /// The input could not be found or read.
+    Input(String),
+    Parse(StepError),
+}
+
+/// Why parsing or a part has no result.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum StepError {
+    Solve(SolveError),
+    /// The solver panicked, with the panic message.
+    Panic(String),
+    /// The solver did not finish within the timeout, and was abandoned.
+    Timeout(Duration),
+}
+
+impl StepError {
+    /// The label shown in place of an answer.
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            StepError::Solve(_) => "ERROR",
+            StepError::Panic(_) => "PANIC",
+            StepError::Timeout(_) => "TIMEOUT",
+        }
+    }
+}
+
+impl Display for StepError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepError::Solve(e) => Display::fmt(e, f),
+            StepError::Panic(message) => write!(f, "panicked: {}", message),
+            StepError::Timeout(timeout) => write!(f, "no result within {:.2?}", timeout),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct PartResult {
+    pub(crate) part: u8,
+    pub(crate) elapsed: Duration,
+    /// The allocations of the part, if they are counted.
+    pub(crate) allocations: Option<Allocations>,
+    pub(crate) answer: Result<Answer, StepError>,
+}
+
+/// The outcome of running the selected parts of one day.
+#[derive(Debug)]
+pub(crate) struct DayResult {
+    pub(crate) year: u16,
+    pub(crate) day: u8,
+    /// Time spent parsing the input, zero if it could not be read.
+    pub(crate) parse: Duration,
+    /// The allocations made while parsing, if they are counted.
+    pub(crate) parse_allocations: Option<Allocations>,
+    pub(crate) failure: Option<Failure>,
+    /// The results of the selected parts, empty on failure.
+    pub(crate) parts: Vec<PartResult>,
+}
+
+impl DayResult {
+    pub(crate) fn failed(&self) -> bool {
+        self.failure.is_some() || self.parts.iter().any(|part| part.answer.is_err())
+    }
+
+    /// Time spent parsing and solving.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.parse + self.parts.iter().map(|part| part.elapsed).sum::<Duration>()
+    }
+}
+
+/// Returns the parts to run: `part`, or both parts.
+pub(crate) fn parts(part: Option<u8>) -> Vec<u8> {
+    [1, 2].into_iter().filter(|p| part.map(|part| part == *p).unwrap_or(true)).collect()
+}
+
+/// What to run of each day.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Options<'a> {
+    /// The name of the solver to run.
+    pub(crate) variant: &'a str,
+    pub(crate) parts: &'a [u8],
+    /// The longest a step may take.
+    pub(crate) timeout: Option<Duration>,
+}
+
+/// Resolves the input of `day` in `year`, and solves it as given by `options`.
+pub(crate) fn run_day(year: u16, day: u8, resolver: &Resolver, options: Options) -> DayResult {
+    let (mut result, parsed) = parse_day(year, day, resolver, options);
+    if let Some(parsed) = parsed {
+        for &part in options.parts {
+            result.parts.push(solve_part(year, day, &parsed, part, options.timeout));
+        }
+    }
+    result
+}
+
+/// A parsed input, shared by the threads solving its parts.
+type Shared = Arc<Box<dyn Parsed + Send + Sync>>;
+
+/// Resolves and parses the input of `day`, returning the result of the day without its parts, and the parsed input
+/// to solve them from unless it failed.
+fn parse_day(year: u16, day: u8, resolver: &Resolver, options: Options) -> (DayResult, Option<Shared>) {
+    match resolver.resolve(year, day) {
+        Ok(inp) => {
+            let solver = registry::find(year, day).and_then(|puzzle| puzzle.variant(options.variant)).expect("registered variant");
+            parse(year, day, solver, inp, options.timeout)
+        }
+        Err(message) => {
+            let result = DayResult {
+                year,
+                day,
+                parse: Duration::ZERO,
+                parse_allocations: None,
+                failure: Some(Failure::Input(message)),
+                parts: vec![],
+            };
+            (result, None)
+        }
+    }
+}
+
+/// What a solver thread reports for a step: parsing, or solving a part.
+pub(crate) struct Step<T> {
+    pub(crate) elapsed: Duration,
+    pub(crate) allocations: Option<Allocations>,
+    pub(crate) result: Result<T, StepError>,
+}
+
+/// Parses `inp` once with `solver` and solves `parts` against it.
+///
+/// Every step runs on its own thread, see [`run_step`], and a part that panics or times out doesn't keep the
+/// other part from being solved.
+pub(crate) fn solve(
+    year: u16,
+    day: u8,
+    solver: &'static (dyn DynSolver + Sync),
+    inp: &str,
+    parts: &[u8],
+    timeout: Option<Duration>,
+) -> DayResult {
+    let (mut result, parsed) = parse(year, day, solver, inp.to_string(), timeout);
+    if let Some(parsed) = parsed {
+        for &part in parts {
+            result.parts.push(solve_part(year, day, &parsed, part, timeout));
+        }
+    }
+    result
+}
+
+/// Parses `inp` with `solver`, returning the result of the day without its parts, and the parsed input unless
+/// parsing failed.
+fn parse(
+    year: u16,
+    day: u8,
+    solver: &'static (dyn DynSolver + Sync),
+    inp: String,
+    timeout: Option<Duration>,
+) -> (DayResult, Option<Shared>) {
+    let step = run_step(year, day, None, timeout, move || solver.parse(&inp).map(Arc::new));
+    let mut result = DayResult { year, day, parse: step.elapsed, parse_allocations: step.allocations, failure: None, parts: vec![] };
+    match step.result {
+        Ok(parsed) => (result, Some(parsed)),
+        Err(e) => {
+            result.failure = Some(Failure::Parse(e));
+            (result, None)
+        }
+    }
+}
+
+fn solve_part(year: u16, day: u8, parsed: &Shared, part: u8, timeout: Option<Duration>) -> PartResult {
+    let parsed = parsed.clone();
+    let step = run_step(year, day, Some(part), timeout, move || if part == 1 { parsed.pt1() } else { parsed.pt2() });
+    PartResult { part, elapsed: step.elapsed, allocations: step.allocations, answer: step.result }
+}
+
+/// Runs parsing, or the given part, on its own thread, so that a panic is reported as [`StepError::Panic`], and a
+/// step that takes longer than `timeout` as [`StepError::Timeout`]. A step that timed out can't be stopped, so it is
+/// left running.
+///
+/// The step runs in tracing spans of the day and the step, which label the events of an explained day.
+pub(crate) fn run_step<T: Send + 'static>(
+    year: u16,
+    day: u8,
+    part: Option<u8>,
+    timeout: Option<Duration>,
+    f: impl FnOnce() -> Result<T, SolveError> + Send + 'static,
+) -> Step<T> {
+    quiet_panics();
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name(SOLVER_THREAD.to_string())
+        .spawn(move || {
+            let _day = tracing::debug_span!("day", year, day).entered();
+            let _step = match part {
+                Some(part) => tracing::debug_span!("part", part),
+                None => tracing::debug_span!("parse"),
+            }
+            .entered();
+            let now = Instant::now();
+            let (result, allocations) = allocations::track(|| panic::catch_unwind(AssertUnwindSafe(f)));
+            let elapsed = now.elapsed();
+            let result = match result {
+                Ok(result) => result.map_err(StepError::Solve),
+                Err(payload) => Err(StepError::Panic(panic_message(payload.as_ref()))),
+            };
+            // Nobody is waiting for a step that timed out.
+            let _ = sender.send(Step { elapsed, allocations, result });
+        })
+        .expect("spawned a solver thread");
+    match timeout {
+        Some(timeout) => receiver
+            .recv_timeout(timeout)
+            .unwrap_or(Step { elapsed: timeout, allocations: None, result: Err(StepError::Timeout(timeout)) }),
+        None => receiver.recv().expect("solver thread reports its step"),
+    }
+}
+
+const SOLVER_THREAD: &str = "solver";
+
+/// Keeps the default panic hook from printing the panics of solvers, which are reported with their results.
+fn quiet_panics() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if thread::current().name() != Some(SOLVER_THREAD) {
+                default(info);
+            }
+        }));
+    });
+}
+
+/// Returns the message of a panic, which is a `&str` or `String` unless panicked with another payload.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-text panic payload".to_string())
+}
+
+/// Work for the threads of [`run_days`]: parsing the day at an index of the days, after which its parts are queued.
+enum Job {
+    Parse(usize),
+    Part { index: usize, year: u16, day: u8, part: u8, parsed: Shared },
+}
+
+/// The finished steps of [`Job`]s.
+enum Done {
+    Parsed(usize, DayResult),
+    Part(usize, PartResult),
+}
+
+/// The jobs waiting for a thread, and whether all days are done.
+struct Queue {
+    jobs: Mutex<(VecDeque<Job>, bool)>,
+    ready: Condvar,
+}
+
+impl Queue {
+    /// Waits for the next job, or returns `None` when all days are done.
+    fn next(&self) -> Option<Job> {
+        let mut jobs = self.jobs.lock().unwrap();
+        loop {
+            if let Some(job) = jobs.0.pop_front() {
+                return Some(job);
+            }
+            if jobs.1 {
+                return None;
+            }
+            jobs = self.ready.wait(jobs).unwrap();
+        }
+    }
+}
+
+/// Runs `days` of `year` on up to `jobs` threads, passing each result to `report` in the order of `days`.
+///
+/// Parsing a day and each of its parts are separate jobs, so that the parts of a day are solved in parallel too.
+pub(crate) fn run_days(year: u16, days: &[u8], resolver: &Resolver, options: Options, jobs: usize, mut report: impl FnMut(DayResult)) {
+    if jobs <= 1 {
+        days.iter().for_each(|day| report(run_day(year, *day, resolver, options)));
+        return;
+    }
+    run_jobs(days.len(), jobs, options, |index| parse_day(year, days[index], resolver, options), report);
+}
+
+/// Runs `count` days on `jobs` threads, parsing each with `parse` and solving its parts as separate jobs, and passes
+/// each result to `report` in the order of the days.
+fn run_jobs(
+    count: usize,
+    jobs: usize,
+    options: Options,
+    parse: impl Fn(usize) -> (DayResult, Option<Shared>) + Sync,
+    mut report: impl FnMut(DayResult),
+) {
+    if count == 0 {
+        return;
+    }
+    let queue = Queue { jobs: Mutex::new(((0..count).map(Job::Parse).collect(), false)), ready: Condvar::new() };
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(count * options.parts.len().max(1)) {
+            let sender = sender.clone();
+            let queue = &queue;
+            let parse = &parse;
+            scope.spawn(move || {
+                while let Some(job) = queue.next() {
+                    let done = match job {
+                        Job::Parse(index) => {
+                            let (result, parsed) = parse(index);
+                            if let Some(parsed) = parsed {
+                                // Finish the days that started before starting new ones.
+                                let mut jobs = queue.jobs.lock().unwrap();
+                                for &part in options.parts.iter().rev() {
+                                    let (year, day, parsed) = (result.year, result.day, parsed.clone());
+                                    jobs.0.push_front(Job::Part { index, year, day, part, parsed });
+                                }
+                                queue.ready.notify_all();
+                            }
+                            Done::Parsed(index, result)
+                        }
+                        Job::Part { index, year, day, part, parsed } => {
+                            Done::Part(index, solve_part(year, day, &parsed, part, options.timeout))
+                        }
+                    };
+                    sender.send(done).unwrap();
+                }
+            });
+        }
+        drop(sender);
+
+        // Hold back days that finish early until every earlier day has been reported.
+        let mut results: Vec<Option<DayResult>> = (0..count).map(|_| None).collect();
+        let mut parts: Vec<Vec<PartResult>> = (0..count).map(|_| vec![]).collect();
+        let mut reported = 0;
+        while reported < count {
+            match receiver.recv().expect("a thread is running a job") {
+                Done::Parsed(index, result) => results[index] = Some(result),
+                Done::Part(index, part) => parts[index].push(part),
+            }
+            while let Some(result) = results[reported].as_mut() {
+                if result.failure.is_none() && parts[reported].len() < options.parts.len() {
+                    break;
+                }
+                let mut result = results[reported].take().unwrap();
+                result.parts = std::mem::take(&mut parts[reported]);
+                result.parts.sort_by_key(|part| options.parts.iter().position(|p| *p == part.part));
+                report(result);
+                reported += 1;
+                if reported == count {
+                    break;
+                }
+            }
+        }
+        queue.jobs.lock().unwrap().1 = true;
+        queue.ready.notify_all();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::DEFAULT_VARIANT;
+
+    #[test]
+    fn test_parts() {
+        assert_eq!(parts(None), vec![1, 2]);
+        assert_eq!(parts(Some(2)), vec![2]);
+    }
+
+    #[test]
+    fn test_run_day() {
+        let path = std::env::temp_dir().join(format!("adventofcode-2021-runner-{}.txt", std::process::id()));
+        std::fs::write(&path, "16,1,2,0,4,2,7,1,2,14").unwrap();
+        let resolver = Resolver { path: Some(path.display().to_string()), dir: std::env::temp_dir(), embedded: false };
+
+        let options = Options { variant: DEFAULT_VARIANT, parts: &[2], timeout: None };
+        let result = run_day(2021, 7, &resolver, options);
+        assert!(!result.failed());
+        assert_eq!(result.parts.len(), 1);
+        assert_eq!(result.parts[0].answer, Ok(Answer::Integer(168)));
+        let result = run_day(2021, 7, &resolver, Options { variant: "median", ..options });
+        assert_eq!(result.parts[0].answer, Ok(Answer::Integer(168)));
+
+        let result = run_day(2021, 1, &resolver, Options { parts: &[1, 2], ..options });
+        assert!(matches!(result.failure, Some(Failure::Parse(_))));
+        assert!(result.parts.is_empty());
+    }
+
+    /// Panics on part 1 when the input is 0, and otherwise sleeps for that many milliseconds.
+    struct Faulty {}
+
+    impl crate::problem::Solver for Faulty {
+        type Parsed = u64;
+
+        fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {
+            Ok(inp.parse().expect("milliseconds"))
+        }
+        fn pt1(&self, millis: &Self::Parsed) -> Result<Answer, SolveError> {
+            assert_ne!(*millis, 0, "no time to sleep");
+            thread::sleep(Duration::from_millis(*millis));
+            Ok(Answer::from(*millis))
+        }
+        fn pt2(&self, millis: &Self::Parsed) -> Result<Answer, SolveError> {
+            Ok(Answer::from(*millis))
+        }
+    }
+
+    #[test]
+    fn test_solve_panic() {
+        let result = solve(2021, 1, &Faulty {}, "0", &[1, 2], None);
+        assert!(matches!(&result.parts[0].answer, Err(StepError::Panic(message)) if message.contains("no time to sleep")));
+        assert_eq!(result.parts[1].answer, Ok(Answer::Integer(0)));
+        assert!(result.failed());
+
+        let result = solve(2021, 1, &Faulty {}, "soon", &[1, 2], None);
+        assert!(matches!(&result.failure, Some(Failure::Parse(StepError::Panic(message))) if message.starts_with("milliseconds")));
+        assert!(result.parts.is_empty());
+    }
+
+    #[test]
+    fn test_solve_timeout() {
+        let timeout = Duration::from_millis(50);
+        let result = solve(2021, 1, &Faulty {}, "5000", &[1, 2], Some(timeout));
+        assert_eq!(result.parts[0].answer, Err(StepError::Timeout(timeout)));
+        assert_eq!(result.parts[0].elapsed, timeout);
+        // The part after the runaway one is still solved.
+        assert_eq!(result.parts[1].answer, Ok(Answer::Integer(5000)));
+
+        let result = solve(2021, 1, &Faulty {}, "1", &[1], Some(Duration::from_secs(10)));
+        assert_eq!(result.parts[0].answer, Ok(Answer::Integer(1)));
+    }
+
+    #[test]
+    fn test_solve_runaway() {
+        // A step that never ends is left behind once it times out.
+        let timeout = Duration::from_millis(50);
+        let step = run_step(2021, 1, Some(1), Some(timeout), || -> Result<(), SolveError> {
+            loop {
+                thread::park();
+            }
+        });
+        assert_eq!(step.result, Err(StepError::Timeout(timeout)));
+
+        // A step that recursed until the stack overflowed would abort the whole run, which no thread can contain, so
+        // the cave systems with endless paths are rejected while parsing instead.
+        let puzzle = registry::find(2021, 12).unwrap();
+        for solver in [puzzle.solver, puzzle.variant("counting").unwrap()] {
+            let result = solve(2021, 12, solver, "start-A\nA-B\nB-end", &[1, 2], Some(timeout));
+            assert!(matches!(&result.failure, Some(Failure::Parse(StepError::Solve(_)))), "{:?}", result.failure);
+            assert!(result.parts.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_solve_allocations() {
+        let result = solve(2021, 1, &Faulty {}, "1", &[2], None);
+        assert_eq!(result.parse_allocations.is_some(), cfg!(feature = "count-allocations"));
+        assert_eq!(result.parts[0].allocations.is_some(), cfg!(feature = "count-allocations"));
+    }
+
+    #[test]
+    fn test_step_error_display() {
+        assert_eq!(StepError::Panic("index out of bounds".to_string()).to_string(), "panicked: index out of bounds");
+        assert_eq!(StepError::Timeout(Duration::from_secs(2)).to_string(), "no result within 2.00s");
+        assert_eq!(StepError::Timeout(Duration::from_secs(2)).label(), "TIMEOUT");
+    }
+
+    #[test]
+    fn test_run_days_in_order() {
+        let resolver = Resolver { path: None, dir: std::env::temp_dir().join("adventofcode-2021-missing"), embedded: true };
+        let days = [9, 1, 4, 2, 7, 3];
+        let options = Options { variant: DEFAULT_VARIANT, parts: &[2, 1], timeout: None };
+        let answers = |jobs| {
+            let mut reported = vec![];
+            run_days(2021, &days, &resolver, options, jobs, |result| {
+                let parts: Vec<(u8, Answer)> = result.parts.into_iter().map(|part| (part.part, part.answer.unwrap())).collect();
+                reported.push((result.day, parts));
+            });
+            reported
+        };
+        let sequential = answers(1);
+        assert_eq!(sequential.iter().map(|(day, _)| *day).collect::<Vec<u8>>(), days);
+        assert!(sequential.iter().all(|(_, parts)| parts.iter().map(|(part, _)| *part).eq([2, 1])));
+        for jobs in [4, 16] {
+            assert_eq!(answers(jobs), sequential);
+        }
+    }
+
+    /// Waits for the parts of two days to be solved at the same time.
+    struct Waiting {}
+
+    static WAITING: std::sync::LazyLock<std::sync::Barrier> = std::sync::LazyLock::new(|| std::sync::Barrier::new(4));
+
+    impl crate::problem::Solver for Waiting {
+        type Parsed = ();
+
+        fn parse(&self, _inp: &str) -> Result<Self::Parsed, SolveError> {
+            Ok(())
+        }
+        fn pt1(&self, _parsed: &Self::Parsed) -> Result<Answer, SolveError> {
+            WAITING.wait();
+            Ok(Answer::from(1_u64))
+        }
+        fn pt2(&self, _parsed: &Self::Parsed) -> Result<Answer, SolveError> {
+            WAITING.wait();
+            Ok(Answer::from(2_u64))
+        }
+    }
+
+    #[test]
+    fn test_run_jobs_in_parallel() {
+        // The parts only pass the barrier when all four of them run at once, and time out otherwise.
+        let options = Options { variant: DEFAULT_VARIANT, parts: &[1, 2], timeout: Some(Duration::from_secs(10)) };
+        let mut reported = vec![];
+        run_jobs(2, 4, options, |index| parse(2021, index as u8 + 1, &Waiting {}, String::new(), options.timeout), |result| {
+            reported.push((result.day, result.parts.into_iter().map(|part| part.answer).collect::<Vec<_>>()));
+        });
+        let answers = vec![Ok(Answer::Integer(1)), Ok(Answer::Integer(2))];
+        assert_eq!(reported, vec![(1, answers.clone()), (2, answers)]);
+    }
+}
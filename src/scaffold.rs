@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+
+/// The file holding the `puzzles!` list of day modules, relative to the repository root.
+pub(crate) const REGISTRY_FILE: &str = "src/lib.rs";
+const REGISTRY_START: &str = "registry::puzzles!(";
+/// The year of the modules named just `dayNN`. Days of other years are prefixed, as in `y2022_day01`.
+const HOME_YEAR: u16 = 2021;
+
+/// Creates the module, an empty input and the registration of a new day in the repository at `root`.
+/// Returns the created and changed files.
+pub(crate) fn new_day(root: &Path, year: u16, day: u8, title: &str) -> Result<Vec<PathBuf>, String> {
+    let prefix = if year == HOME_YEAR { String::new() } else { format!("y{}_", year) };
+    let module = format!("{}day{:02}", prefix, day);
+    let input = format!("{}input{:02}.txt", prefix, day);
+    let module_path = root.join("src").join(format!("{}.rs", module));
+    let input_path = root.join("src").join(&input);
+    let registry_path = root.join(REGISTRY_FILE);
+
+    let registry = std::fs::read_to_string(&registry_path)
+        .map_err(|e| format!("Failed to read '{}' (run from the repository root): {}", registry_path.display(), e))?;
+    let registry = register(&registry, &module)?;
+    for path in [&module_path, &input_path] {
+        if path.exists() {
+            return Err(format!("{} day {:02} already exists: '{}'", year, day, path.display()));
+        }
+    }
+
+    std::fs::write(&module_path, module_source(year, day, title, &input))
+        .and_then(|_| std::fs::write(&input_path, ""))
+        .and_then(|_| std::fs::write(&registry_path, registry))
+        .map_err(|e| format!("Failed to scaffold {} day {:02}: {}", year, day, e))?;
+    Ok(vec![module_path, input_path, registry_path])
+}
+
+/// Adds `module` to the `puzzles!` list in `source`, keeping the list sorted.
+fn register(source: &str, module: &str) -> Result<String, String> {
+    let missing = || format!("No '{}' list found in {}", REGISTRY_START, REGISTRY_FILE);
+    let start = source.find(REGISTRY_START).ok_or_else(missing)? + REGISTRY_START.len();
+    let end = start + source[start..].find(");").ok_or_else(missing)?;
+
+    let mut modules: Vec<&str> = source[start..end].split(',').map(|m| m.trim()).filter(|m| !m.is_empty()).collect();
+    if modules.contains(&module) {
+        return Err(format!("{} is already registered in {}", module, REGISTRY_FILE));
+    }
+    modules.push(module);
+    modules.sort_unstable();
+
+    let list: String = modules.iter().map(|m| format!("\n    {},", m)).collect();
+    Ok(format!("{}{}\n{}", &source[..start], list, &source[end..]))
+}
+
+fn module_source(year: u16, day: u8, title: &str, input: &str) -> String {
+    format!(
+        r#"//! [Day {day}: {title}](https://adventofcode.com/{year}/day/{day}).
+
+use crate::problem::{{Answer, SolveError, Solver}};
+use crate::registry::puzzle;
+
+const DAY: u8 = {day};
+
+/// The solver of day {day}.
+pub struct Day {{}}
+
+puzzle!(year: {year}, day: DAY, title: "{title}", solver: Day {{}}, input: "{input}");
+
+impl Solver for Day {{
+    type Parsed = Vec<String>;
+
+    fn parse(&self, inp: &str) -> Result<Self::Parsed, SolveError> {{
+        Ok(inp.lines().map(|line| line.to_string()).collect())
+    }}
+    fn pt1(&self, _lines: &Self::Parsed) -> Result<Answer, SolveError> {{
+        Err(SolveError::unsolvable(DAY, "not solved yet"))
+    }}
+    fn pt2(&self, _lines: &Self::Parsed) -> Result<Answer, SolveError> {{
+        Err(SolveError::unsolvable(DAY, "not solved yet"))
+    }}
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    fn example_input() -> &'static str {{
+        ""
+    }}
+
+    #[test]
+    #[ignore = "not solved yet"]
+    fn test_pt1_example() {{
+        assert_eq!(Day {{}}.solve_pt1(example_input()), Ok(Answer::Integer(0)))
+    }}
+
+    #[test]
+    #[ignore = "not solved yet"]
+    fn test_pt1() {{
+        assert_eq!(Day {{}}.solve_pt1(input()), Ok(Answer::Integer(0)))
+    }}
+
+    #[test]
+    #[ignore = "not solved yet"]
+    fn test_pt2_example() {{
+        assert_eq!(Day {{}}.solve_pt2(example_input()), Ok(Answer::Integer(0)))
+    }}
+
+    #[test]
+    #[ignore = "not solved yet"]
+    fn test_pt2() {{
+        assert_eq!(Day {{}}.solve_pt2(input()), Ok(Answer::Integer(0)))
+    }}
+}}
+"#,
+        year = year,
+        day = day,
+        title = title.escape_debug(),
+        input = input,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "pub mod problem;
+
+registry::puzzles!(
+    day01,
+    day02,
+    day10,
+);
+
+pub use app::run;
+";
+
+    #[test]
+    fn test_register() {
+        assert_eq!(
+            register(LIB, "day03"),
+            Ok(LIB.replace("    day02,\n", "    day02,\n    day03,\n"))
+        );
+        assert_eq!(register("registry::puzzles!(day01);", "day02"), Ok("registry::puzzles!(\n    day01,\n    day02,\n);".to_string()));
+        assert!(register(LIB, "day10").is_err());
+        assert!(register("pub mod problem;", "day03").is_err());
+    }
+
+    #[test]
+    fn test_new_day() {
+        let root = std::env::temp_dir().join(format!("adventofcode-2021-scaffold-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join(REGISTRY_FILE), LIB).unwrap();
+
+        let created = new_day(&root, 2021, 16, "Packet \"Decoder\"").unwrap();
+        assert_eq!(created.len(), 3);
+        let module = std::fs::read_to_string(root.join("src/day16.rs")).unwrap();
+        assert!(module.contains("const DAY: u8 = 16;"), "{}", module);
+        assert!(module.contains(r#"puzzle!(year: 2021, day: DAY, title: "Packet \"Decoder\"", solver: Day {}, input: "input16.txt");"#), "{}", module);
+        assert!(module.contains("fn test_pt1_example() {") && module.contains("fn test_pt2() {"), "{}", module);
+        assert_eq!(module.matches("#[ignore = \"not solved yet\"]").count(), 4, "{}", module);
+        assert_eq!(std::fs::read_to_string(root.join("src/input16.txt")).unwrap(), "");
+        assert!(std::fs::read_to_string(root.join(REGISTRY_FILE)).unwrap().contains("    day10,\n    day16,\n);"));
+
+        // An existing day is never overwritten.
+        std::fs::write(root.join("src/day16.rs"), "// solved").unwrap();
+        assert!(new_day(&root, 2021, 16, "Packet Decoder").is_err());
+        assert_eq!(std::fs::read_to_string(root.join("src/day16.rs")).unwrap(), "// solved");
+        std::fs::write(root.join("src/day03.rs"), "// solved").unwrap();
+        assert!(new_day(&root, 2021, 3, "Binary Diagnostic").is_err());
+        assert!(!root.join("src/input03.txt").exists());
+
+        // Days of other years get their own modules.
+        new_day(&root, 2022, 3, "Rucksack Reorganization").unwrap();
+        let module = std::fs::read_to_string(root.join("src/y2022_day03.rs")).unwrap();
+        assert!(module.contains(r#"puzzle!(year: 2022, day: DAY, title: "Rucksack Reorganization", solver: Day {}, input: "y2022_input03.txt");"#), "{}", module);
+        assert!(root.join("src/y2022_input03.txt").exists());
+        assert!(std::fs::read_to_string(root.join(REGISTRY_FILE)).unwrap().contains("    day16,\n    y2022_day03,\n);"));
+    }
+
+    fn copy_dir(from: &Path, to: &Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in std::fs::read_dir(from).unwrap() {
+            let path = entry.unwrap().path();
+            let target = to.join(path.file_name().unwrap());
+            if path.is_dir() { copy_dir(&path, &target) } else { std::fs::copy(&path, &target).map(|_| ()).unwrap() }
+        }
+    }
+
+    #[test]
+    fn test_new_day_builds() {
+        // Scaffolds a day in a copy of the crate, which must build and pass its tests until the day is solved.
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let root = std::env::temp_dir().join(format!("adventofcode-2021-scaffold-build-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        for file in ["Cargo.toml", "Cargo.lock", "build.rs"] {
+            if crate_dir.join(file).exists() {
+                std::fs::copy(crate_dir.join(file), root.join(file)).unwrap();
+            }
+        }
+        for dir in ["src", "answers", "tests"] {
+            copy_dir(&crate_dir.join(dir), &root.join(dir));
+        }
+        new_day(&root, 2099, 1, "Scaffolded").unwrap();
+
+        let output = std::process::Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+            .args(["test", "--offline", "--lib", "y2099_day01::"])
+            .current_dir(&root)
+            // A target directory of its own, kept between runs so that only the copy is built again.
+            .env("CARGO_TARGET_DIR", crate_dir.join("target").join("scaffold"))
+            .output()
+            .unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{}\n{}", stdout, String::from_utf8_lossy(&output.stderr));
+        assert!(stdout.contains("0 passed; 0 failed; 4 ignored"), "{}", stdout);
+    }
+
+    #[test]
+    fn test_module_source_matches_template() {
+        // The generated module has the layout of the existing days.
+        let source = module_source(2021, 1, "Sonar Sweep", "input01.txt");
+        let day01 = include_str!("day01.rs");
+        for line in ["//! [Day 1: Sonar Sweep](https://adventofcode.com/2021/day/1).", "const DAY: u8 = 1;", "/// The solver of day 1.", "impl Solver for Day {"] {
+            assert!(source.contains(line) && day01.contains(line), "{}", line);
+        }
+    }
+}
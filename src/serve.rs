@@ -0,0 +1,377 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use crate::cli::Serve;
+use crate::problem::SolveError;
+use crate::registry;
+use crate::report::{json_answer, json_string};
+use crate::runner::{self, Failure, StepError};
+
+/// The most bytes of request line and headers read before giving up on a request.
+const MAX_HEAD: u64 = 8 * 1024;
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An HTTP response with a JSON body.
+#[derive(Debug, Eq, PartialEq)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    /// An error response, `{"error": {"kind": ..., "message": ...}}`, with the location of parse errors.
+    fn error(status: u16, kind: &str, message: &str) -> Response {
+        Response { status, body: format!("{{\"error\": {{\"kind\": \"{}\", \"message\": {}}}}}", kind, json_string(message)) }
+    }
+
+    /// The response to a failed step: the input could not be parsed or solved, the solver panicked or timed out.
+    fn step_error(error: &StepError) -> Response {
+        match error {
+            StepError::Solve(SolveError::Parse { line, column, .. }) => Response {
+                status: 422,
+                body: format!(
+                    "{{\"error\": {{\"kind\": \"parse\", \"message\": {}, \"line\": {}, \"column\": {}}}}}",
+                    json_string(&error.to_string()),
+                    line,
+                    column
+                ),
+            },
+            StepError::Solve(SolveError::Unsolvable { .. }) => Response::error(422, "unsolvable", &error.to_string()),
+            StepError::Panic(_) => Response::error(500, "panic", &error.to_string()),
+            StepError::Timeout(_) => Response::error(504, "timeout", &error.to_string()),
+        }
+    }
+
+    fn write_to(&self, stream: &mut impl Write) -> std::io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            411 => "Length Required",
+            413 => "Content Too Large",
+            422 => "Unprocessable Content",
+            500 => "Internal Server Error",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            _ => "",
+        };
+        let allow = if self.status == 405 { "Allow: POST\r\n" } else { "" };
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            self.body.len(),
+            allow,
+            self.body
+        )?;
+        stream.flush()
+    }
+}
+
+/// One of the `--jobs` requests the server solves at once, given back when dropped.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    /// Takes a slot of `active`, unless all `jobs` are taken.
+    fn acquire(active: &Arc<AtomicUsize>, jobs: usize) -> Option<Slot> {
+        if active.fetch_add(1, Ordering::SeqCst) >= jobs {
+            active.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Slot(Arc::clone(active)))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+/// Reads a request with a body of at most `max_body` bytes, or returns the response to refuse it with.
+fn read_request(stream: impl Read, max_body: usize) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let bad_request = |message: &str| Response::error(400, "request", message);
+    let mut head = (&mut reader).take(MAX_HEAD);
+    let mut line = String::new();
+    head.read_line(&mut line).map_err(|_| bad_request("Malformed request line"))?;
+    let (method, path) = match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        [method, path, version] if version.starts_with("HTTP/1.") => (method.to_string(), path.to_string()),
+        _ => return Err(bad_request("Malformed request line")),
+    };
+
+    let mut content_length = None;
+    loop {
+        line.clear();
+        if head.read_line(&mut line).map_err(|_| bad_request("Malformed headers"))? == 0 || !line.ends_with('\n') {
+            return Err(bad_request("Incomplete or too long headers"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header.split_once(':').ok_or_else(|| bad_request("Malformed header"))?;
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = Some(value.trim().parse::<usize>().map_err(|_| bad_request("Invalid Content-Length"))?);
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return Err(Response::error(411, "request", "Send the input with a Content-Length instead of a Transfer-Encoding"));
+        }
+    }
+
+    let length = match (method.as_str(), content_length) {
+        ("POST", None) => return Err(Response::error(411, "request", "Missing Content-Length")),
+        (_, length) => length.unwrap_or(0),
+    };
+    if length > max_body {
+        return Err(Response::error(413, "request", &format!("The input is larger than {} bytes", max_body)));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|_| bad_request("Incomplete body"))?;
+    let body = String::from_utf8(body).map_err(|_| bad_request("The input is not UTF-8"))?;
+    Ok(Request { method, path, body })
+}
+
+/// Returns the year, day and part of `/<year>/day/<day>/part/<part>`, or the response to an unknown path.
+fn route(path: &str) -> Result<(u16, u8, u8), Response> {
+    let not_found = || Response::error(404, "request", &format!("Unknown path: '{}', expected /<year>/day/<day>/part/<1|2>", path));
+    match path.trim_end_matches('/').split('/').collect::<Vec<&str>>().as_slice() {
+        ["", year, "day", day, "part", part @ ("1" | "2")] => {
+            let (year, day, part) = (year.parse().map_err(|_| not_found())?, day.parse().map_err(|_| not_found())?, part.parse().unwrap());
+            match registry::find(year, day) {
+                Some(_) => Ok((year, day, part)),
+                None => Err(Response::error(404, "request", &format!("No solver for {} day {}", year, day))),
+            }
+        }
+        _ => Err(not_found()),
+    }
+}
+
+/// Solves the part of the puzzle at the path of `request`, with its body as the input, on a solver thread that holds
+/// the `slot` until it finishes. A solver that runs over the `timeout` is answered with `504`, but keeps the slot.
+fn handle(request: &Request, timeout: Option<Duration>, slot: Slot) -> Response {
+    let (year, day, part) = match route(&request.path) {
+        Ok(route) => route,
+        Err(response) => return response,
+    };
+    if request.method != "POST" {
+        return Response::error(405, "request", &format!("Unsupported method: {}, post the input instead", request.method));
+    }
+
+    let solver = registry::find(year, day).expect("routed day").solver;
+    // Only the end is trimmed, so that the lines and columns of parse errors are those of the body as it was sent.
+    let inp = request.body.trim_end().to_string();
+    let (sender, receiver) = mpsc::channel();
+    let spawned = std::thread::Builder::new().name("solver".to_string()).spawn(move || {
+        let _ = sender.send(runner::solve(year, day, solver, &inp, &[part], None));
+        drop(slot);
+    });
+    if let Err(e) = spawned {
+        return Response::error(500, "request", &e.to_string());
+    }
+    let result = match timeout {
+        Some(timeout) => match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(_) => return Response::step_error(&StepError::Timeout(timeout)),
+        },
+        None => receiver.recv().expect("the solver thread sends its result"),
+    };
+    match (&result.failure, result.parts.first()) {
+        (Some(Failure::Parse(e)), _) => Response::step_error(e),
+        (Some(Failure::Input(message)), _) => Response::error(400, "request", message),
+        (None, Some(solved)) => match &solved.answer {
+            Ok(answer) => Response {
+                status: 200,
+                body: format!(
+                    "{{\"year\": {}, \"day\": {}, \"part\": {}, \"answer\": {}, \"parse_ns\": {}, \"duration_ns\": {}}}",
+                    year,
+                    day,
+                    part,
+                    json_answer(answer),
+                    result.parse.as_nanos(),
+                    solved.elapsed.as_nanos()
+                ),
+            },
+            Err(e) => Response::step_error(e),
+        },
+        (None, None) => unreachable!("the part was run on the parsed input"),
+    }
+}
+
+/// Reads one request from `stream`, and answers it in the `slot`.
+fn respond(mut stream: TcpStream, max_body: usize, timeout: Option<Duration>, slot: Slot) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let response = match stream.try_clone() {
+        Ok(reader) => match read_request(reader, max_body) {
+            Ok(request) => handle(&request, timeout, slot),
+            Err(response) => response,
+        },
+        Err(e) => Response::error(500, "request", &e.to_string()),
+    };
+    let _ = response.write_to(&mut stream);
+}
+
+/// Answers the connections to `listener` while fewer than `serve.jobs` requests are being read or solved, including
+/// those whose solvers timed out but are still running, and refuses connections beyond that.
+fn listen(listener: TcpListener, serve: &Serve) {
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let Some(slot) = Slot::acquire(&active, serve.jobs) else {
+            let _ = Response::error(503, "busy", &format!("Already solving {} inputs, try again later", serve.jobs)).write_to(&mut stream);
+            continue;
+        };
+        let (max_body, timeout) = (serve.max_body, serve.timeout);
+        std::thread::spawn(move || respond(stream, max_body, timeout, slot));
+    }
+}
+
+/// Serves the solvers over HTTP on `serve.address` until the process is stopped. Returns false if it could not
+/// listen there.
+pub(crate) fn run(serve: &Serve) -> bool {
+    let listener = match TcpListener::bind(&serve.address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to listen on '{}': {}", serve.address, e);
+            return false;
+        }
+    };
+    match listener.local_addr() {
+        Ok(address) => println!("serving on http://{}, POST the input to /<year>/day/<day>/part/<1|2>", address),
+        Err(_) => println!("serving on http://{}", serve.address),
+    }
+    listen(listener, serve);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(path: &str, body: &str) -> Request {
+        Request { method: "POST".to_string(), path: path.to_string(), body: body.to_string() }
+    }
+
+    fn handle(request: &Request, timeout: Option<Duration>) -> Response {
+        super::handle(request, timeout, Slot::acquire(&Arc::default(), 1).unwrap())
+    }
+
+    #[test]
+    fn test_read_request() {
+        let request = "POST /2021/day/7/part/1 HTTP/1.1\r\nHost: localhost\r\ncontent-length: 5\r\n\r\n16,1,2";
+        assert_eq!(read_request(request.as_bytes(), 100), Ok(post("/2021/day/7/part/1", "16,1,")));
+
+        let status = |request: &[u8]| read_request(request, 4).unwrap_err().status;
+        assert_eq!(status(b"POST /2021/day/7/part/1 HTTP/1.1\r\nContent-Length: 5\r\n\r\n16,1,"), 413);
+        assert_eq!(status(b"POST /2021/day/7/part/1 HTTP/1.1\r\n\r\n16,1,"), 411);
+        assert_eq!(status(b"POST /2021/day/7/part/1 HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"), 411);
+        assert_eq!(status(b"POST /2021/day/7/part/1 HTTP/1.1\r\nContent-Length: 3\r\n\r\n1"), 400);
+        assert_eq!(status(b"POST /2021/day/7/part/1 HTTP/1.1\r\nContent-Length: 2\r\n\r\n\xff\xfe"), 400);
+        assert_eq!(status(b"POST /2021/day/7/part/1\r\n\r\n"), 400);
+        assert_eq!(status(format!("GET / HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "x".repeat(MAX_HEAD as usize)).as_bytes()), 400);
+    }
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route("/2021/day/15/part/2"), Ok((2021, 15, 2)));
+        assert_eq!(route("/2021/day/15/part/2/"), Ok((2021, 15, 2)));
+        assert_eq!(route("/2021/day/15/part/3").unwrap_err().status, 404);
+        assert_eq!(route("/2021/day/26/part/1").unwrap_err().status, 404);
+        assert_eq!(route("/").unwrap_err().status, 404);
+    }
+
+    #[test]
+    fn test_handle() {
+        assert_eq!(
+            handle(&post("/2021/day/7/part/2", "16,1,2,0,4,2,7,1,2,14\n"), None).body.split(", \"parse_ns\"").next(),
+            Some("{\"year\": 2021, \"day\": 7, \"part\": 2, \"answer\": 168")
+        );
+        assert_eq!(
+            handle(&post("/2021/day/7/part/1", "16,1,O"), None),
+            Response {
+                status: 422,
+                body: "{\"error\": {\"kind\": \"parse\", \"message\": \"day 07, line 1, column 6: invalid digit found in string ('O')\", \
+                       \"line\": 1, \"column\": 6}}"
+                    .to_string()
+            }
+        );
+        // Lines and columns are those of the body as sent, counting its leading blank lines.
+        let response = handle(&post("/2021/day/13/part/1", "\n\n0,0\n4,0\n\nfold along x=2\n"), None);
+        assert_eq!((response.status, response.body.contains("\"line\": 3, \"column\": 1")), (422, true), "{}", response.body);
+        // Folding the dots off the paper would draw an image of gigabytes.
+        assert_eq!(handle(&post("/2021/day/13/part/2", "0,0\n32767,32767\n\nfold along y=40000"), None).status, 422);
+        let get = Request { method: "GET".to_string(), ..post("/2021/day/7/part/1", "") };
+        assert_eq!(handle(&get, None).status, 405);
+    }
+
+    #[test]
+    fn test_handle_timeout() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let slot = Slot::acquire(&active, 1).unwrap();
+        assert!(Slot::acquire(&active, 1).is_none());
+        let request = post("/2021/day/15/part/2", crate::day15::input());
+        let response = super::handle(&request, Some(Duration::from_millis(1)), slot);
+        assert_eq!(response.status, 504);
+        // The solver that timed out keeps its slot until it finishes.
+        assert!(Slot::acquire(&active, 1).is_none());
+        while active.load(Ordering::SeqCst) > 0 {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(Slot::acquire(&active, 1).is_some());
+    }
+
+    #[test]
+    fn test_listen() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let serve = Serve { address: address.to_string(), ..Serve::default() };
+        std::thread::spawn(move || listen(listener, &serve));
+
+        let url = format!("http://{}/2021/day/13/part/2", address);
+        let response = ureq::post(&url).send_string("0,0\n3,0\n\nfold along x=2").unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(response.into_string().unwrap().contains("\"answer\": \"##\""));
+
+        let url = format!("http://{}/2021/day/13/part/1", address);
+        match ureq::post(&url).send_string("0,0\n4;0\n\nfold along x=2") {
+            Err(ureq::Error::Status(422, response)) => assert!(response.into_string().unwrap().contains("\"kind\": \"parse\"")),
+            other => panic!("expected a parse error, got {:?}", other.map(|response| response.status())),
+        }
+    }
+
+    #[test]
+    fn test_listen_endless_paths() {
+        // A cave system with endless paths once overflowed the stack of its solver, which aborted the whole server.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let serve = Serve { address: address.to_string(), ..Serve::default() };
+        std::thread::spawn(move || listen(listener, &serve));
+
+        for part in [1, 2] {
+            let url = format!("http://{}/2021/day/12/part/{}", address, part);
+            match ureq::post(&url).send_string("start-A\nA-B\nB-end") {
+                Err(ureq::Error::Status(422, response)) => assert!(response.into_string().unwrap().contains("endless paths")),
+                other => panic!("expected a parse error, got {:?}", other.map(|response| response.status())),
+            }
+        }
+        let url = format!("http://{}/2021/day/12/part/1", address);
+        let response = ureq::post(&url).send_string("start-A\nA-end").unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(response.into_string().unwrap().contains("\"answer\": 1"));
+    }
+}
@@ -0,0 +1,358 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::cli::Submit;
+use crate::client::Client;
+use crate::input::Resolver;
+use crate::problem::Answer;
+use crate::registry;
+use crate::runner::{self, Failure};
+
+/// Default location of the ledger of submitted answers.
+pub(crate) const DEFAULT_LEDGER: &str = "submissions.tsv";
+
+/// The verdict of the website on a submitted answer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Outcome {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// An answer was submitted too recently, with the time left to wait if given.
+    RateLimited(Option<String>),
+    /// The part is already solved, or not unlocked yet.
+    WrongLevel,
+    /// A response that is not recognized, as text.
+    Unknown(String),
+}
+
+impl Outcome {
+    /// Parses the response page of an answer submission.
+    pub(crate) fn parse(page: &str) -> Outcome {
+        let text = article_text(page);
+        if text.contains("That's the right answer") {
+            Outcome::Right
+        } else if text.contains("You gave an answer too recently") {
+            let wait = text
+                .split_once("You have ")
+                .and_then(|(_, rest)| rest.split_once(" left to wait"))
+                .map(|(wait, _)| wait.to_string());
+            Outcome::RateLimited(wait)
+        } else if text.contains("That's not the right answer") {
+            if text.contains("your answer is too high") {
+                Outcome::TooHigh
+            } else if text.contains("your answer is too low") {
+                Outcome::TooLow
+            } else {
+                Outcome::Wrong
+            }
+        } else if text.contains("You don't seem to be solving the right level") {
+            Outcome::WrongLevel
+        } else {
+            Outcome::Unknown(text)
+        }
+    }
+
+    /// The name of the outcome in the ledger, for outcomes worth remembering.
+    fn name(&self) -> Option<&'static str> {
+        match self {
+            Outcome::Right => Some("right"),
+            Outcome::Wrong => Some("wrong"),
+            Outcome::TooHigh => Some("too-high"),
+            Outcome::TooLow => Some("too-low"),
+            _ => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Outcome> {
+        [Outcome::Right, Outcome::Wrong, Outcome::TooHigh, Outcome::TooLow]
+            .into_iter()
+            .find(|outcome| outcome.name() == Some(name))
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Right => write!(f, "right answer"),
+            Outcome::Wrong => write!(f, "wrong answer"),
+            Outcome::TooHigh => write!(f, "wrong answer, too high"),
+            Outcome::TooLow => write!(f, "wrong answer, too low"),
+            Outcome::RateLimited(Some(wait)) => write!(f, "rate limited, {} left to wait", wait),
+            Outcome::RateLimited(None) => write!(f, "rate limited"),
+            Outcome::WrongLevel => write!(f, "already solved or not unlocked yet"),
+            Outcome::Unknown(text) => write!(f, "unrecognized response: {}", text),
+        }
+    }
+}
+
+/// Returns the text of the `<article>` of a page, without tags and with collapsed whitespace.
+fn article_text(page: &str) -> String {
+    let article = page
+        .split_once("<article")
+        .and_then(|(_, rest)| rest.split_once("</article>"))
+        .map(|(article, _)| article.split_once('>').map(|(_, article)| article).unwrap_or(article))
+        .unwrap_or(page);
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Entry {
+    year: u16,
+    day: u8,
+    part: u8,
+    outcome: Outcome,
+    answer: String,
+}
+
+/// The answers submitted so far, and their outcomes.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct Ledger {
+    entries: Vec<Entry>,
+}
+
+const LEDGER_HEADER: &str = "year\tday\tpart\toutcome\tanswer";
+
+impl Ledger {
+    /// Reads the ledger at `path`, or an empty ledger if there is none yet.
+    pub(crate) fn load(path: &Path) -> Result<Ledger, String> {
+        match std::fs::read_to_string(path) {
+            Ok(inp) => Ledger::parse(&inp).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Ledger::default()),
+            Err(e) => Err(format!("Failed to read '{}': {}", path.display(), e)),
+        }
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_string()).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+    }
+
+    fn parse(inp: &str) -> Result<Ledger, String> {
+        let mut ledger = Ledger::default();
+        for (number, line) in inp.lines().enumerate() {
+            if line.trim().is_empty() || line == LEDGER_HEADER {
+                continue;
+            }
+            let malformed = || format!("line {}: malformed ledger entry '{}'", number + 1, line);
+            let fields: Vec<&str> = line.splitn(5, '\t').collect();
+            if fields.len() != 5 {
+                return Err(malformed());
+            }
+            ledger.entries.push(Entry {
+                year: fields[0].parse().map_err(|_| malformed())?,
+                day: fields[1].parse().map_err(|_| malformed())?,
+                part: fields[2].parse().map_err(|_| malformed())?,
+                outcome: Outcome::from_name(fields[3]).ok_or_else(malformed)?,
+                answer: fields[4].to_string(),
+            });
+        }
+        Ok(ledger)
+    }
+
+    /// Records `outcome` if it is worth remembering.
+    pub(crate) fn record(&mut self, year: u16, day: u8, part: u8, answer: &str, outcome: &Outcome) {
+        if outcome.name().is_some() {
+            self.entries.push(Entry { year, day, part, outcome: outcome.clone(), answer: answer.to_string() });
+        }
+    }
+
+    /// Returns the known correct answer of a part.
+    pub(crate) fn right(&self, year: u16, day: u8, part: u8) -> Option<&str> {
+        self.entries(year, day, part).find(|entry| entry.outcome == Outcome::Right).map(|entry| entry.answer.as_str())
+    }
+
+    /// Returns why `answer` is known to be wrong, if it is.
+    pub(crate) fn refute(&self, year: u16, day: u8, part: u8, answer: &str) -> Option<String> {
+        if let Some(entry) = self.entries(year, day, part).find(|entry| entry.answer == answer) {
+            return Some(format!("{} was already submitted: {}", answer, entry.outcome));
+        }
+        let value = answer.parse::<i64>().ok()?;
+        self.entries(year, day, part).find_map(|entry| {
+            let bound = entry.answer.parse::<i64>().ok()?;
+            match entry.outcome {
+                Outcome::TooHigh if value >= bound => Some(format!("{} is not below {}, which was too high", answer, bound)),
+                Outcome::TooLow if value <= bound => Some(format!("{} is not above {}, which was too low", answer, bound)),
+                _ => None,
+            }
+        })
+    }
+
+    fn entries(&self, year: u16, day: u8, part: u8) -> impl Iterator<Item=&Entry> {
+        self.entries.iter().filter(move |entry| entry.year == year && entry.day == day && entry.part == part)
+    }
+}
+
+impl fmt::Display for Ledger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", LEDGER_HEADER)?;
+        for entry in &self.entries {
+            writeln!(f, "{}\t{}\t{}\t{}\t{}", entry.year, entry.day, entry.part, entry.outcome.name().unwrap(), entry.answer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the text to submit for `answer`. Images have to be read and submitted by hand.
+pub(crate) fn answer_text(answer: &Answer) -> Result<String, String> {
+    match answer {
+        Answer::Image(_) => Err("Image answers can't be submitted, read the letters and pass them with --answer".to_string()),
+        answer => Ok(answer.to_string()),
+    }
+}
+
+/// Submits `answer` unless the ledger already knows the outcome, and records the outcome.
+pub(crate) fn submit(
+    client: impl FnOnce() -> Result<Client, String>,
+    ledger: &mut Ledger,
+    year: u16,
+    day: u8,
+    part: u8,
+    answer: &str,
+) -> Result<Outcome, String> {
+    if let Some(right) = ledger.right(year, day, part) {
+        return if right == answer {
+            Ok(Outcome::Right)
+        } else {
+            Err(format!("{} day {:02} part {} is already solved with {}, not {}", year, day, part, right, answer))
+        };
+    }
+    if let Some(reason) = ledger.refute(year, day, part, answer) {
+        return Err(format!("Refusing to submit a known wrong answer: {}", reason));
+    }
+    let outcome = Outcome::parse(&client()?.answer(year, day, part, answer)?);
+    ledger.record(year, day, part, answer, &outcome);
+    Ok(outcome)
+}
+
+/// Solves the part, unless given an answer, and submits it. Returns false unless the answer is right.
+pub(crate) fn run(submit: &Submit) -> bool {
+    let (day, part) = (submit.day, submit.part);
+    let year = submit.year.unwrap_or_else(registry::latest_year);
+    let answer = match &submit.answer {
+        Some(answer) => Ok(answer.clone()),
+        None => solve(year, day, part, submit.input.clone()),
+    };
+    let ledger_path = Path::new(submit.ledger.as_deref().unwrap_or(DEFAULT_LEDGER));
+    let result = answer.and_then(|answer| {
+        let mut ledger = Ledger::load(ledger_path)?;
+        let outcome = self::submit(|| Client::from_env(submit.base_url.clone()), &mut ledger, year, day, part, &answer)?;
+        ledger.save(ledger_path)?;
+        Ok((answer, outcome))
+    });
+    match result {
+        Ok((answer, outcome)) => {
+            println!("day{:02} - pt{}: {:>15} {}", day, part, answer, outcome);
+            outcome == Outcome::Right
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            false
+        }
+    }
+}
+
+fn solve(year: u16, day: u8, part: u8, input: Option<String>) -> Result<String, String> {
+    if registry::find(year, day).is_none() {
+        return Err(format!("No solver for {} day {:02}: pass the answer with --answer", year, day));
+    }
+    let mut result = runner::run_day(
+        year,
+        day,
+        &Resolver::from_env(input),
+        runner::Options { variant: registry::DEFAULT_VARIANT, parts: &[part], timeout: None },
+    );
+    match (result.failure, result.parts.pop()) {
+        (Some(Failure::Input(message)), _) => Err(message),
+        (Some(Failure::Parse(e)), _) => Err(e.to_string()),
+        (None, Some(part)) => answer_text(&part.answer.map_err(|e| e.to_string())?),
+        (None, None) => unreachable!("a part was run"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::tests::mock_server;
+
+    fn page(article: &str) -> String {
+        format!("<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>", article)
+    }
+
+    #[test]
+    fn test_parse_outcome() {
+        assert_eq!(Outcome::parse(&page("That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer.")), Outcome::Right);
+        assert_eq!(
+            Outcome::parse(&page("That's not the right answer; your answer is too high.  If you're stuck, ...")),
+            Outcome::TooHigh
+        );
+        assert_eq!(Outcome::parse(&page("That's not the right answer; your answer is too low.")), Outcome::TooLow);
+        assert_eq!(
+            Outcome::parse(&page("That's not the right answer.  If you're stuck, make sure you're using the full input data")),
+            Outcome::Wrong
+        );
+        assert_eq!(
+            Outcome::parse(&page("You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 41s left to wait.")),
+            Outcome::RateLimited(Some("41s".to_string()))
+        );
+        assert_eq!(Outcome::parse(&page("You don't seem to be solving the right level.  Did you already complete it?")), Outcome::WrongLevel);
+        assert_eq!(Outcome::parse(&page("Something <em>else</em>")), Outcome::Unknown("Something else".to_string()));
+    }
+
+    #[test]
+    fn test_ledger_roundtrip() {
+        let mut ledger = Ledger::default();
+        ledger.record(2021, 7, 1, "351900", &Outcome::TooLow);
+        ledger.record(2021, 7, 1, "351901", &Outcome::Right);
+        ledger.record(2021, 7, 2, "1", &Outcome::RateLimited(None));
+        assert_eq!(ledger.entries.len(), 2);
+        assert_eq!(Ledger::parse(&ledger.to_string()), Ok(ledger));
+        assert!(Ledger::parse("2021\t7\t1\tmaybe\t5").is_err());
+        assert!(Ledger::parse("7\t1\tright\t5").is_err());
+    }
+
+    #[test]
+    fn test_refute() {
+        let mut ledger = Ledger::default();
+        ledger.record(2021, 7, 2, "200", &Outcome::TooHigh);
+        ledger.record(2021, 7, 2, "100", &Outcome::TooLow);
+        ledger.record(2021, 7, 2, "150", &Outcome::Wrong);
+        assert_eq!(ledger.refute(2021, 7, 2, "150"), Some("150 was already submitted: wrong answer".to_string()));
+        assert_eq!(ledger.refute(2021, 7, 2, "250"), Some("250 is not below 200, which was too high".to_string()));
+        assert_eq!(ledger.refute(2021, 7, 2, "99"), Some("99 is not above 100, which was too low".to_string()));
+        assert_eq!(ledger.refute(2021, 7, 2, "168"), None);
+        assert_eq!(ledger.refute(2021, 7, 1, "250"), None);
+        assert_eq!(ledger.refute(2020, 7, 2, "250"), None);
+    }
+
+    #[test]
+    fn test_submit() {
+        let (base_url, server) = mock_server(vec![
+            ("200 OK", "<article><p>That's not the right answer; your answer is too low.</p></article>"),
+            ("200 OK", "<article><p>That's the right answer!</p></article>"),
+        ]);
+        let client = || Ok(Client::new(&base_url, "cookie"));
+        let mut ledger = Ledger::default();
+
+        assert_eq!(submit(client, &mut ledger, 2021, 7, 2, "100"), Ok(Outcome::TooLow));
+        // Refused without a request: the mock only answers twice.
+        assert!(submit(client, &mut ledger, 2021, 7, 2, "90").is_err());
+        assert_eq!(submit(client, &mut ledger, 2021, 7, 2, "168"), Ok(Outcome::Right));
+        assert_eq!(submit(client, &mut ledger, 2021, 7, 2, "168"), Ok(Outcome::Right));
+        assert!(submit(client, &mut ledger, 2021, 7, 2, "169").is_err());
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /2021/day/7/answer HTTP/1.1\r\n"), "{}", requests[0]);
+        assert!(requests[0].ends_with("\r\n\r\nlevel=2&answer=100"), "{}", requests[0]);
+        assert!(requests[1].ends_with("level=2&answer=168"), "{}", requests[1]);
+    }
+}
@@ -0,0 +1,466 @@
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Cell, Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::cli::Tui;
+use crate::history::{self, Build};
+use crate::input::Resolver;
+use crate::problem::Answer;
+use crate::registry;
+use crate::report;
+use crate::runner::{self, DayResult, Failure};
+
+/// How long to wait for a key before checking for finished days again.
+const TICK: Duration = Duration::from_millis(100);
+/// The number of recent timings drawn per part.
+const TREND: usize = 12;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Status {
+    /// Waiting to be run.
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+impl Status {
+    fn style(&self) -> Style {
+        match self {
+            Status::Pending => Style::default().fg(Color::DarkGray),
+            Status::Running => Style::default().fg(Color::Yellow),
+            Status::Done => Style::default().fg(Color::Green),
+            Status::Failed => Style::default().fg(Color::Red),
+        }
+    }
+}
+
+/// A row of the table: a registered day and its last run.
+struct DayRow {
+    day: u8,
+    title: &'static str,
+    status: Status,
+    /// The answers of the last run, or what went wrong.
+    answers: [Option<String>; 2],
+    error: Option<String>,
+    /// The recent timings of each part, oldest first: those in the history, then those of this session.
+    timings: [Vec<Duration>; 2],
+}
+
+/// What the worker thread reports.
+enum Message {
+    /// The day is being run along with the other days sent with it.
+    Started(u8),
+    Finished(DayResult),
+}
+
+/// What a key asks for, besides moving around.
+#[derive(Debug, Eq, PartialEq)]
+enum Action {
+    None,
+    Quit,
+    Run(Vec<u8>),
+    Visualize(u8),
+}
+
+/// A text shown on top of the table, such as the visualization of a day.
+struct Popup {
+    title: String,
+    text: String,
+    scroll: u16,
+}
+
+struct App {
+    days: Vec<DayRow>,
+    table: TableState,
+    popup: Option<Popup>,
+    /// A problem to show below the table, such as a history that couldn't be written.
+    notice: Option<String>,
+}
+
+impl App {
+    /// Lists `days` of `year` as pending, with the timings of `trends` from the history.
+    fn new(year: u16, days: &[u8], trends: &[history::Trend]) -> App {
+        let days = days
+            .iter()
+            .map(|&day| {
+                let timings = [1, 2].map(|part| {
+                    let step = format!("pt{}", part);
+                    trends
+                        .iter()
+                        .find(|trend| trend.day == day && trend.variant == registry::DEFAULT_VARIANT && trend.step == step)
+                        .map(|trend| trend.recent.clone())
+                        .unwrap_or_default()
+                });
+                DayRow {
+                    day,
+                    title: registry::find(year, day).map(|puzzle| puzzle.title).unwrap_or_default(),
+                    status: Status::Pending,
+                    answers: [None, None],
+                    error: None,
+                    timings,
+                }
+            })
+            .collect();
+        App { days, table: TableState::default().with_selected(Some(0)), popup: None, notice: None }
+    }
+
+    fn selected(&self) -> &DayRow {
+        &self.days[self.table.selected().unwrap_or(0)]
+    }
+
+    /// Handles a key press, returning what to do about it.
+    fn key(&mut self, code: KeyCode) -> Action {
+        if let Some(popup) = &mut self.popup {
+            match code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('v') | KeyCode::Enter => self.popup = None,
+                KeyCode::Up | KeyCode::Char('k') => popup.scroll = popup.scroll.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => popup.scroll = popup.scroll.saturating_add(1),
+                KeyCode::PageUp => popup.scroll = popup.scroll.saturating_sub(20),
+                KeyCode::PageDown => popup.scroll = popup.scroll.saturating_add(20),
+                _ => {}
+            }
+            return Action::None;
+        }
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::Quit,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.table.select(Some(self.table.selected().unwrap_or(0).saturating_sub(1)));
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.table.select(Some((self.table.selected().unwrap_or(0) + 1).min(self.days.len() - 1)));
+                Action::None
+            }
+            KeyCode::Char('r') => self.queue(vec![self.selected().day]),
+            KeyCode::Char('a') => self.queue(self.days.iter().map(|row| row.day).collect()),
+            KeyCode::Char('v') | KeyCode::Enter => Action::Visualize(self.selected().day),
+            _ => Action::None,
+        }
+    }
+
+    /// Marks `days` as pending, returning the action to run those that aren't pending already.
+    fn queue(&mut self, days: Vec<u8>) -> Action {
+        let mut queued = vec![];
+        for row in self.days.iter_mut().filter(|row| days.contains(&row.day) && row.status != Status::Pending) {
+            row.status = Status::Pending;
+            queued.push(row.day);
+        }
+        Action::Run(queued)
+    }
+
+    fn row_mut(&mut self, day: u8) -> Option<&mut DayRow> {
+        self.days.iter_mut().find(|row| row.day == day)
+    }
+
+    fn apply(&mut self, message: Message) {
+        match message {
+            Message::Started(day) => {
+                if let Some(row) = self.row_mut(day) {
+                    row.status = Status::Running;
+                }
+            }
+            Message::Finished(result) => {
+                let row = match self.row_mut(result.day) {
+                    Some(row) => row,
+                    None => return,
+                };
+                row.status = if result.failed() { Status::Failed } else { Status::Done };
+                row.answers = [None, None];
+                row.error = match &result.failure {
+                    Some(Failure::Input(message)) => Some(message.clone()),
+                    Some(Failure::Parse(e)) => Some(e.to_string()),
+                    None => None,
+                };
+                for part in &result.parts {
+                    let i = part.part as usize - 1;
+                    match &part.answer {
+                        Ok(answer) => {
+                            row.answers[i] = Some(match answer {
+                                Answer::Image(rows) => report::image_size(rows),
+                                answer => answer.to_string(),
+                            });
+                            row.timings[i].push(part.elapsed);
+                            let excess = row.timings[i].len().saturating_sub(TREND);
+                            row.timings[i].drain(..excess);
+                        }
+                        Err(e) => {
+                            row.answers[i] = Some(e.label().to_string());
+                            row.error.get_or_insert_with(|| format!("part {}: {}", part.part, e));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, footer_area] = Layout::vertical([Constraint::Min(3), Constraint::Length(2)]).areas(frame.area());
+        let header = Row::new(["day", "title", "status", "part 1", "time", "trend", "part 2", "time", "trend"])
+            .style(Style::default().add_modifier(Modifier::BOLD));
+        let rows = self.days.iter().map(|row| {
+            let mut cells = vec![
+                Cell::from(format!("day{:02}", row.day)),
+                Cell::from(row.title),
+                Cell::from(format!("{:?}", row.status).to_lowercase()).style(row.status.style()),
+            ];
+            for i in 0..2 {
+                let timings = &row.timings[i];
+                cells.push(Cell::from(row.answers[i].clone().unwrap_or_default()));
+                cells.push(Cell::from(timings.last().map(|elapsed| format!("{:.2?}", elapsed)).unwrap_or_default()));
+                cells.push(Cell::from(history::sparkline(timings)));
+            }
+            Row::new(cells)
+        });
+        let widths = [
+            Constraint::Length(5),
+            Constraint::Fill(1),
+            Constraint::Length(7),
+            Constraint::Length(16),
+            Constraint::Length(10),
+            Constraint::Length(TREND as u16),
+            Constraint::Length(16),
+            Constraint::Length(10),
+            Constraint::Length(TREND as u16),
+        ];
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered().title(" Advent of Code "))
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+
+        let keys = "↑↓ select  r run again  a run all again  v visualize  q quit";
+        let detail = self.notice.clone().or_else(|| self.selected().error.clone()).unwrap_or_default();
+        frame.render_widget(Paragraph::new(format!("{}\n{}", detail, keys)), footer_area);
+
+        if let Some(popup) = &self.popup {
+            let area = centered(frame.area(), 90, 80);
+            frame.render_widget(Clear, area);
+            let text = Paragraph::new(popup.text.as_str())
+                .block(Block::bordered().title(format!(" {} (Esc to close) ", popup.title)))
+                .scroll((popup.scroll, 0));
+            frame.render_widget(text, area);
+        }
+    }
+}
+
+/// Returns the area of `percent_x` by `percent_y` percent in the middle of `area`.
+fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect { x: area.x + (area.width - width) / 2, y: area.y + (area.height - height) / 2, width, height }
+}
+
+/// Parses the input of `day` and draws it, or tells why it can't be drawn.
+fn visualization(year: u16, day: u8, resolver: &Resolver) -> String {
+    let solver = registry::find(year, day).expect("listed day").solver;
+    let inp = match resolver.resolve(year, day) {
+        Ok(inp) => inp,
+        Err(message) => return message,
+    };
+    match solver.parse(&inp) {
+        Ok(parsed) => parsed.visualize().unwrap_or_else(|| format!("day {:02} has no visualization", day)),
+        Err(e) => e.to_string(),
+    }
+}
+
+/// Runs the days sent to it on a thread of its own, on up to `jobs` threads as `run` does. Days sent while others
+/// run are run together once those are done.
+fn spawn_worker(year: u16, timeout: Option<Duration>, jobs: usize) -> (Sender<Vec<u8>>, Receiver<Message>) {
+    let (requests, received) = mpsc::channel::<Vec<u8>>();
+    let (send, messages) = mpsc::channel();
+    std::thread::spawn(move || {
+        let resolver = Resolver::from_env(None);
+        let options = runner::Options { variant: registry::DEFAULT_VARIANT, parts: &[1, 2], timeout };
+        while let Ok(mut days) = received.recv() {
+            days.extend(received.try_iter().flatten());
+            days.sort_unstable();
+            days.dedup();
+            if days.iter().any(|&day| send.send(Message::Started(day)).is_err()) {
+                return;
+            }
+            runner::run_days(year, &days, &resolver, options, jobs, |result| {
+                let _ = send.send(Message::Finished(result));
+            });
+        }
+    });
+    (requests, messages)
+}
+
+/// Shows every day of `year` in a full-screen table, running them all, until `q` is pressed. Returns false if the
+/// terminal failed.
+pub(crate) fn run(tui: &Tui, year: u16) -> bool {
+    let days = registry::days(year);
+    if days.is_empty() {
+        eprintln!("No solvers for {}", year);
+        return false;
+    }
+    let build = Build::current(tui.jobs);
+    let path = Path::new(tui.history.as_deref().unwrap_or(history::DEFAULT_PATH));
+    let (entries, notice) = match history::load(path) {
+        Ok(entries) => (entries, None),
+        Err(message) => (vec![], Some(message)),
+    };
+    let mut app = App::new(year, &days, &history::trends(&entries, &build, year, &days, TREND - 1));
+    app.notice = notice;
+    let resolver = Resolver::from_env(None);
+    let (requests, messages) = spawn_worker(year, tui.timeout, tui.jobs);
+    let _ = requests.send(days.clone());
+
+    let mut terminal = ratatui::init();
+    let result = (|| -> std::io::Result<()> {
+        loop {
+            while let Ok(message) = messages.try_recv() {
+                if let Message::Finished(result) = &message {
+                    let entries = history::entries(history::timestamp(), &build, registry::DEFAULT_VARIANT, std::slice::from_ref(result));
+                    if let Err(message) = history::append(path, &entries) {
+                        app.notice = Some(message);
+                    }
+                }
+                app.apply(message);
+            }
+            terminal.draw(|frame| app.draw(frame))?;
+            if !event::poll(TICK)? {
+                continue;
+            }
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+            match app.key(key.code) {
+                Action::None => {}
+                Action::Quit => return Ok(()),
+                Action::Run(days) => {
+                    let _ = requests.send(days);
+                }
+                Action::Visualize(day) => {
+                    app.popup = Some(Popup {
+                        title: format!("day{:02}: {}", day, app.selected().title),
+                        text: visualization(year, day, &resolver),
+                        scroll: 0,
+                    })
+                }
+            }
+        }
+    })();
+    ratatui::restore();
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::SolveError;
+    use crate::runner::{PartResult, StepError};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn finished(day: u8, answers: [Result<Answer, StepError>; 2]) -> Message {
+        let parts = answers
+            .into_iter()
+            .enumerate()
+            .map(|(i, answer)| PartResult { part: i as u8 + 1, elapsed: Duration::from_millis(i as u64 + 1), allocations: None, answer })
+            .collect();
+        Message::Finished(DayResult { year: 2021, day, parse: Duration::ZERO, parse_allocations: None, failure: None, parts })
+    }
+
+    fn screen(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut app = App::new(2021, &[1, 2], &[]);
+        assert!(app.days.iter().all(|row| row.status == Status::Pending));
+        app.apply(Message::Started(1));
+        assert_eq!(app.days[0].status, Status::Running);
+
+        app.apply(finished(1, [Ok(Answer::Integer(7)), Ok(Answer::Integer(5))]));
+        assert_eq!(app.days[0].status, Status::Done);
+        assert_eq!(app.days[0].answers, [Some("7".to_string()), Some("5".to_string())]);
+        assert_eq!(app.days[0].timings, [vec![Duration::from_millis(1)], vec![Duration::from_millis(2)]]);
+
+        app.apply(finished(2, [Ok(Answer::Integer(150)), Err(StepError::Solve(SolveError::unsolvable(2, "lost")))]));
+        assert_eq!(app.days[1].status, Status::Failed);
+        assert_eq!(app.days[1].answers[1], Some("ERROR".to_string()));
+        assert_eq!(app.days[1].error, Some("part 2: day 02: lost".to_string()));
+    }
+
+    #[test]
+    fn test_worker() {
+        let (requests, messages) = spawn_worker(2021, None, 4);
+        requests.send(vec![2, 1]).unwrap();
+        let started: Vec<u8> = (0..2).map(|_| match messages.recv().unwrap() {
+            Message::Started(day) => day,
+            Message::Finished(result) => panic!("day {} finished before the others started", result.day),
+        }).collect();
+        let finished: Vec<u8> = (0..2).map(|_| match messages.recv().unwrap() {
+            Message::Finished(result) => result.day,
+            Message::Started(day) => panic!("day {} started twice", day),
+        }).collect();
+        assert_eq!((started, finished), (vec![1, 2], vec![1, 2]));
+    }
+
+    #[test]
+    fn test_key() {
+        let mut app = App::new(2021, &[1, 2, 3], &[]);
+        // Pending days aren't queued twice.
+        assert_eq!(app.key(KeyCode::Char('r')), Action::Run(vec![]));
+        app.apply(finished(1, [Ok(Answer::Integer(7)), Ok(Answer::Integer(5))]));
+        assert_eq!(app.key(KeyCode::Char('a')), Action::Run(vec![1]));
+        assert_eq!(app.days[0].status, Status::Pending);
+
+        assert_eq!(app.key(KeyCode::Down), Action::None);
+        assert_eq!(app.key(KeyCode::Down), Action::None);
+        assert_eq!(app.key(KeyCode::Down), Action::None);
+        assert_eq!(app.key(KeyCode::Char('v')), Action::Visualize(3));
+        app.popup = Some(Popup { title: String::new(), text: String::new(), scroll: 0 });
+        // Keys go to the open popup, until it's closed.
+        assert_eq!(app.key(KeyCode::Char('q')), Action::None);
+        assert!(app.popup.is_none());
+        assert_eq!(app.key(KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut app = App::new(2021, &[1, 13], &[]);
+        app.apply(finished(13, [Ok(Answer::Integer(17)), Ok(Answer::image("#.#\n###"))]));
+        let drawn = screen(&mut app);
+        assert!(drawn.contains("day01"), "{}", drawn);
+        assert!(drawn.contains("Sonar Sweep"));
+        assert!(drawn.contains("pending"));
+        assert!(drawn.contains("done"));
+        assert!(drawn.contains("3x2 image"), "{}", drawn);
+
+        app.popup = Some(Popup { title: "day13".to_string(), text: "#####\n#...#".to_string(), scroll: 0 });
+        assert!(screen(&mut app).contains("#...#"));
+    }
+
+    #[test]
+    fn test_visualization() {
+        let path = std::env::temp_dir().join(format!("adventofcode-2021-tui-{}.txt", std::process::id()));
+        std::fs::write(&path, "11111\n19991\n19191\n19991\n11111").unwrap();
+        let resolver = Resolver { path: Some(path.display().to_string()), dir: std::env::temp_dir(), embedded: false };
+        assert_eq!(visualization(2021, 11, &resolver), "11111\n19991\n19191\n19991\n11111");
+        assert_eq!(visualization(2021, 1, &resolver), "day 01 has no visualization");
+        std::fs::remove_file(path).unwrap();
+    }
+}
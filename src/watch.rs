@@ -0,0 +1,114 @@
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::answers::Answers;
+use crate::cli::Watch;
+use crate::examples;
+use crate::input::Resolver;
+use crate::registry;
+use crate::report;
+use crate::runner;
+
+/// How often the watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The modification time and length of each watched file, `None` while it doesn't exist.
+type Snapshot = Vec<(PathBuf, Option<(SystemTime, u64)>)>;
+
+fn snapshot(paths: &[PathBuf]) -> Snapshot {
+    paths
+        .iter()
+        .map(|path| {
+            let state = std::fs::metadata(path).ok().and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())));
+            (path.clone(), state)
+        })
+        .collect()
+}
+
+/// Solves `day` of `year` whenever its input, or one of its examples with `--examples`, changes. Never returns;
+/// stop it with Ctrl-C.
+pub(crate) fn run(watch: &Watch, year: u16, day: u8) -> ! {
+    let resolver = Resolver::from_env(watch.run.input.clone());
+    let input = watch.run.input.as_ref().map(PathBuf::from).unwrap_or_else(|| resolver.day_path(year, day));
+    let examples_dir = examples::day_dir(Path::new(examples::DEFAULT_EXAMPLES_DIR), year, day);
+    let mut last = None;
+    loop {
+        // Lists the examples again every time, to pick up added and removed ones.
+        let mut paths = vec![input.clone()];
+        if watch.examples {
+            paths.extend(examples::files(&examples_dir));
+        }
+        let current = snapshot(&paths);
+        if last.as_ref() != Some(&current) {
+            show(watch, year, day, &resolver, &paths);
+            last = Some(current);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Clears the terminal and prints the answers to the examples in `paths[1..]`, then those to the input.
+fn show(watch: &Watch, year: u16, day: u8, resolver: &Resolver, paths: &[PathBuf]) {
+    let mut stdout = std::io::stdout();
+    if stdout.is_terminal() {
+        print!("\x1b[2J\x1b[H");
+    }
+    println!("watching {} day{:02}, Ctrl-C to stop", year, day);
+    let variant = watch.run.variant.as_deref().unwrap_or(registry::DEFAULT_VARIANT);
+    let parts = runner::parts(watch.run.part);
+    if watch.examples {
+        let solver = registry::find(year, day).and_then(|puzzle| puzzle.variant(variant)).expect("selected variant");
+        if paths.len() == 1 {
+            println!("\nno examples in '{}'", examples::day_dir(Path::new(examples::DEFAULT_EXAMPLES_DIR), year, day).display());
+        }
+        for path in &paths[1..] {
+            println!("\nexample '{}':", path.display());
+            match std::fs::read_to_string(path) {
+                Ok(inp) => {
+                    let answers = example_answers(path, day);
+                    report::print_table(&runner::solve(year, day, solver, inp.trim(), &parts, watch.run.timeout), answers.as_ref());
+                }
+                Err(e) => eprintln!("Failed to read example '{}': {}", path.display(), e),
+            }
+        }
+    }
+    println!("\ninput '{}':", paths[0].display());
+    let options = runner::Options { variant, parts: &parts, timeout: watch.run.timeout };
+    report::print_table(&runner::run_day(year, day, resolver, options), None);
+    let _ = stdout.flush();
+}
+
+/// Returns the known answers of an example of `day`, if it has any.
+fn example_answers(example: &Path, day: u8) -> Option<Answers> {
+    let path = examples::answers_path(example);
+    if !path.exists() {
+        return None;
+    }
+    Answers::load_day(&path.to_string_lossy(), day).map_err(|message| eprintln!("{}", message)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let path = std::env::temp_dir().join(format!("adventofcode-2021-watch-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let paths = vec![path.clone()];
+        let missing = snapshot(&paths);
+        assert_eq!(missing, vec![(path.clone(), None)]);
+
+        std::fs::write(&path, "16,1,2").unwrap();
+        let created = snapshot(&paths);
+        assert_ne!(created, missing);
+        assert_eq!(snapshot(&paths), created);
+
+        // The length changes even where modification times are too coarse to tell.
+        std::fs::write(&path, "16,1,2,0,4").unwrap();
+        let modified = snapshot(&paths);
+        std::fs::remove_file(&path).unwrap();
+        assert_ne!(modified, created);
+    }
+}